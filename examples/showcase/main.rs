@@ -218,7 +218,11 @@ pub fn player_move(
     move_vector = move_vector.normalize_or_zero();
 
     if keyboard_input.just_pressed(KeyCode::Space) {
-        input.start_jump();
+        if keyboard_input.pressed(KeyCode::ControlLeft) {
+            input.drop_through();
+        } else {
+            input.start_jump();
+        }
     }
     if keyboard_input.just_released(KeyCode::Space) {
         input.stop_jump();
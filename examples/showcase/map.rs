@@ -6,6 +6,7 @@ use bevy::{
     math::primitives::Sphere,
     prelude::*,
 };
+use puppeteer::puppet::OneWayPlatform;
 
 pub fn spawn_map(
     mut commands: Commands,
@@ -145,6 +146,12 @@ pub fn spawn_map(
         Move(Vec3::new(0.4, 0.0, 0.0)),
     ));
 
+    // one-way platform
+    commands.spawn((
+        cube(Transform::from_xyz(4.0, 2.0, 6.0), Vec3::new(3.0, 0.2, 3.0)),
+        OneWayPlatform,
+    ));
+
     let mut cylinder = |transform: Transform,
                         size: Vec3|
      -> (
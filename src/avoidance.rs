@@ -0,0 +1,198 @@
+use std::f32::consts::TAU;
+
+use avian3d::prelude::{ColliderAabb, SpatialQuery};
+use bevy::prelude::*;
+
+use crate::{
+    modifiers::{ModifierStat, MovementModifiers},
+    puppet::Puppet,
+    puppeteer::{Puppeteer, PuppeteerInput},
+};
+
+/// Add this component to an AI driven [`Puppeteer`] to steer around other puppets.
+///
+/// Uses reciprocal velocity obstacles: every tick a set of candidate velocities is sampled
+/// and the one closest to the desired velocity that doesn't collide soon is picked.
+/// Adjusts [`PuppeteerInput::move_direction`] and scales the speed for the current tick,
+/// [`PuppeteerInput::speed_multiplier`] is left untouched.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct Avoidance {
+    /// Distance in which other puppets are considered
+    pub neighbor_distance: f32,
+    /// Maximum number of closest puppets considered
+    pub max_neighbors: usize,
+    /// Collisions further away in time than this are ignored
+    pub time_horizon: f32,
+    /// Number of sampled directions
+    pub samples: u32,
+    /// How much a collision is avoided in favor of moving in the desired direction
+    pub weight: f32,
+}
+
+impl Default for Avoidance {
+    fn default() -> Self {
+        Self {
+            neighbor_distance: 5.0,
+            max_neighbors: 8,
+            time_horizon: 2.0,
+            samples: 16,
+            weight: 2.0,
+        }
+    }
+}
+
+struct Neighbor {
+    position: Vec2,
+    velocity: Vec2,
+    radius: f32,
+}
+
+/// Horizontal radius of a puppet
+fn radius(aabb: &ColliderAabb) -> f32 {
+    (aabb.max - aabb.min).xz().max_element() * 0.5
+}
+
+/// Time until a circle with the given relative position and velocity hits a circle of `radius`
+/// at the origin. Returns [`f32::INFINITY`] if they never collide.
+fn time_to_collision(position: Vec2, velocity: Vec2, radius: f32) -> f32 {
+    if position.length_squared() < radius * radius {
+        // Already overlapping, only moving apart is fine
+        return if velocity.dot(position) < 0.0 {
+            0.0
+        } else {
+            f32::INFINITY
+        };
+    }
+
+    let a = velocity.length_squared();
+    let b = 2.0 * position.dot(velocity);
+    let c = position.length_squared() - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return f32::INFINITY;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if t < 0.0 { f32::INFINITY } else { t }
+}
+
+#[allow(clippy::complexity)]
+pub fn avoid(
+    mut query: Query<(
+        Entity,
+        &Avoidance,
+        &Puppeteer,
+        &mut PuppeteerInput,
+        &Puppet,
+        &MovementModifiers,
+        &Transform,
+        &ColliderAabb,
+    )>,
+    neighbor_query: Query<(&Puppet, &Transform, &ColliderAabb)>,
+    spatial_query: SpatialQuery,
+) {
+    for (entity, avoidance, puppeteer, mut input, puppet, modifiers, transform, aabb) in &mut query
+    {
+        let preferred_velocity = input.move_direction.xz().normalize_or_zero()
+            * modifiers.apply(ModifierStat::MaxSpeed, puppeteer.max_speed)
+            * input.path_multiplier.unwrap_or(input.speed_multiplier);
+        let max_speed = preferred_velocity.length();
+        if max_speed == 0.0 {
+            continue;
+        }
+
+        let position = transform.translation.xz();
+        let velocity = puppet.target_velocity.xz();
+        let radius = radius(aabb);
+
+        let mut neighbors = spatial_query
+            .aabb_intersections_with_aabb(ColliderAabb {
+                min: aabb.min - Vec3::splat(avoidance.neighbor_distance),
+                max: aabb.max + Vec3::splat(avoidance.neighbor_distance),
+            })
+            .into_iter()
+            .filter(|&other| other != entity)
+            .filter_map(|other| neighbor_query.get(other).ok())
+            .map(|(other_puppet, other_transform, other_aabb)| Neighbor {
+                position: other_transform.translation.xz(),
+                velocity: other_puppet.target_velocity.xz(),
+                radius: self::radius(other_aabb),
+            })
+            .filter(|neighbor| {
+                neighbor.position.distance(position) - neighbor.radius
+                    <= avoidance.neighbor_distance
+            })
+            .collect::<Vec<_>>();
+        if neighbors.is_empty() {
+            continue;
+        }
+        neighbors.sort_by(|a, b| {
+            a.position
+                .distance_squared(position)
+                .total_cmp(&b.position.distance_squared(position))
+        });
+        neighbors.truncate(avoidance.max_neighbors);
+
+        let candidates = (0..avoidance.samples)
+            .flat_map(|i| {
+                let direction = Vec2::from_angle(i as f32 / avoidance.samples as f32 * TAU);
+                [direction * max_speed, direction * max_speed * 0.5]
+            })
+            .chain([preferred_velocity, Vec2::ZERO]);
+
+        let mut best_velocity = preferred_velocity;
+        let mut best_cost = f32::INFINITY;
+        for candidate in candidates {
+            // Each puppet takes half of the responsibility to avoid the collision
+            let time = neighbors
+                .iter()
+                .map(|neighbor| {
+                    time_to_collision(
+                        position - neighbor.position,
+                        2.0 * candidate - velocity - neighbor.velocity,
+                        radius + neighbor.radius,
+                    )
+                })
+                .fold(f32::INFINITY, f32::min);
+
+            let collision_cost = if time > avoidance.time_horizon {
+                0.0
+            } else {
+                avoidance.weight / time
+            };
+            let cost = collision_cost + candidate.distance(preferred_velocity);
+            if cost < best_cost {
+                best_cost = cost;
+                best_velocity = candidate;
+            }
+        }
+
+        input.move_direction = Vec3::new(best_velocity.x, 0.0, best_velocity.y).normalize_or_zero();
+        input.avoidance_multiplier = Some(best_velocity.length() / max_speed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn head_on_collides() {
+        let position = Vec2::ZERO - Vec2::new(2.0, 0.0);
+        let time = time_to_collision(position, Vec2::new(1.0, 0.0), 1.0);
+        assert!(
+            (time - 1.0).abs() < 1e-5,
+            "expected a collision after 1s, got {time}"
+        );
+    }
+
+    #[test]
+    fn receding_never_collides() {
+        let position = Vec2::ZERO - Vec2::new(2.0, 0.0);
+        assert_eq!(
+            time_to_collision(position, Vec2::new(-1.0, 0.0), 1.0),
+            f32::INFINITY
+        );
+    }
+}
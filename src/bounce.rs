@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::{
+    puppet::{Grounded, Puppet},
+    puppeteer::FallDamageMultiplier,
+};
+
+/// Launches puppets that land on this collider back into the air.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+#[require(FallDamageMultiplier(0.0))]
+pub struct BouncePad {
+    /// Speed the puppet is launched with
+    pub launch_speed: f32,
+    /// Part of the landing speed that is bounced back, used when it is faster than [`Self::launch_speed`]
+    pub restitution: f32,
+    /// Whether the puppet is launched along the up direction of the pad instead of straight up
+    pub along_normal: bool,
+    /// Horizontal speed added in the direction the puppet is moving
+    pub horizontal_boost: f32,
+}
+
+impl Default for BouncePad {
+    fn default() -> Self {
+        Self {
+            launch_speed: 15.0,
+            restitution: 0.0,
+            along_normal: false,
+            horizontal_boost: 0.0,
+        }
+    }
+}
+
+/// Marker component for a puppet launched by a [`BouncePad`] that is still rising.
+///
+/// A puppeteer doesn't cut the jump off while this is present, so the arc stays the same
+/// whether or not the jump is held.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+#[component(storage = "SparseSet")]
+pub struct Bouncing;
+
+#[allow(clippy::complexity)]
+pub(crate) fn bounce(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Puppet, Option<Ref<Grounded>>, Has<Bouncing>)>,
+    pad_query: Query<(&BouncePad, &GlobalTransform)>,
+) {
+    for (entity, mut puppet, grounded, is_bouncing) in &mut query {
+        if let Some(grounded) = grounded
+            && grounded.is_changed()
+            && puppet.gravity_velocity <= 0.0
+            && let Ok((pad, pad_transform)) = pad_query.get(grounded.0)
+        {
+            let speed = pad
+                .launch_speed
+                .max(-puppet.gravity_velocity * pad.restitution);
+            let direction = if pad.along_normal {
+                pad_transform.up()
+            } else {
+                Dir3::Y
+            };
+            let launch = direction * speed;
+            let horizontal_direction =
+                (puppet.target_velocity * Vec3::new(1.0, 0.0, 1.0)).normalize_or_zero();
+
+            puppet.gravity_velocity = launch.y;
+            puppet.target_velocity +=
+                Vec3::new(launch.x, 0.0, launch.z) + horizontal_direction * pad.horizontal_boost;
+            commands.entity(entity).insert(Bouncing);
+        } else if is_bouncing && puppet.gravity_velocity <= 0.0 {
+            commands.entity(entity).remove::<Bouncing>();
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::puppet::{Grounded, Puppet};
+
+/// Velocity applied to a puppet from outside, like knockback or explosions.
+///
+/// It is moved separately from [`Puppet::target_velocity`], so it isn't overwritten by
+/// input, and slows down with [`Self::ground_drag`] or [`Self::air_drag`].
+/// The vertical part is added to [`Puppet::gravity_velocity`].
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct ExternalVelocity {
+    /// The current velocity, the vertical part is moved to [`Puppet::gravity_velocity`] every tick
+    pub velocity: Vec3,
+    /// How fast the velocity decays per second while grounded
+    pub ground_drag: f32,
+    /// How fast the velocity decays per second while airborne
+    pub air_drag: f32,
+    /// Set by an upward impulse to cancel the fall speed before it is applied
+    cancel_fall: bool,
+}
+
+impl Default for ExternalVelocity {
+    fn default() -> Self {
+        Self {
+            velocity: Vec3::ZERO,
+            ground_drag: 8.0,
+            air_drag: 1.0,
+            cancel_fall: false,
+        }
+    }
+}
+
+impl ExternalVelocity {
+    /// Adds `impulse` to the velocity, independent of [`Puppet::mass`].
+    /// An upward impulse cancels the fall speed of the puppet, so it is launched by the full impulse
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.velocity += impulse;
+        self.cancel_fall |= impulse.y > 0.0;
+    }
+
+    /// The gravity velocity of a puppet after the vertical part of the velocity is applied,
+    /// like [`update_external_velocity`] does
+    pub(crate) fn launch(&self, gravity_velocity: f32) -> f32 {
+        let gravity_velocity = if self.cancel_fall {
+            gravity_velocity.max(0.0)
+        } else {
+            gravity_velocity
+        };
+        gravity_velocity + self.velocity.y
+    }
+}
+
+/// Marker component for a puppet launched upwards by its [`ExternalVelocity`] that is still rising.
+///
+/// Like [`Bouncing`](crate::bounce::Bouncing), a puppeteer doesn't cut the jump off while this is
+/// present, so a launch reaches the same height whether or not the jump is held.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+#[component(storage = "SparseSet")]
+pub struct Launched;
+
+#[allow(clippy::complexity)]
+pub(crate) fn update_external_velocity(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Puppet,
+        &mut ExternalVelocity,
+        Has<Grounded>,
+        Has<Launched>,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, mut puppet, mut external, grounded, is_launched) in &mut query {
+        if external.cancel_fall {
+            external.cancel_fall = false;
+            puppet.gravity_velocity = puppet.gravity_velocity.max(0.0);
+        }
+        if external.velocity.y > 0.0 && puppet.gravity_velocity + external.velocity.y > 0.0 {
+            commands.entity(entity).insert(Launched);
+        } else if is_launched && puppet.gravity_velocity <= 0.0 {
+            commands.entity(entity).remove::<Launched>();
+        }
+        if external.velocity.y != 0.0 {
+            puppet.gravity_velocity += external.velocity.y;
+            external.velocity.y = 0.0;
+        }
+
+        if external.velocity == Vec3::ZERO {
+            continue;
+        }
+
+        let drag = if grounded {
+            external.ground_drag
+        } else {
+            external.air_drag
+        };
+        external.velocity *= (-drag * time.delta_secs()).exp();
+        if external.velocity.length_squared() < 0.0001 {
+            external.velocity = Vec3::ZERO;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use avian3d::prelude::{Collider, GravityScale, Sensor, SpatialQuery, SpatialQueryFilter};
+use bevy::prelude::*;
+
+use crate::{
+    external::ExternalVelocity,
+    puppet::{Grounded, Puppet},
+    puppeteer::Puppeteer,
+};
+
+/// A volume that continuously accelerates puppets inside of it, like wind, fans, vents or currents.
+///
+/// The acceleration is added to the [`ExternalVelocity`] of the puppet, so it is slowed down by
+/// its drag.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+#[require(Sensor)]
+pub struct ForceVolume {
+    /// Direction of the force in the local space of the volume
+    pub direction: Vec3,
+    /// Acceleration at the origin of the volume
+    pub acceleration: f32,
+    /// Distance along [`Self::direction`] from the origin at which the force is gone,
+    /// `None` for the same force everywhere in the volume
+    pub range: Option<f32>,
+    /// How the force fades out over [`Self::range`]
+    pub falloff: EaseFunction,
+    /// Multiplier for the horizontal force on grounded puppets.
+    ///
+    /// Grounded puppets are only lifted by the part of the vertical force that overcomes gravity.
+    pub grounded_multiplier: f32,
+}
+
+impl Default for ForceVolume {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::Y,
+            acceleration: 20.0,
+            range: None,
+            falloff: EaseFunction::Linear,
+            grounded_multiplier: 0.1,
+        }
+    }
+}
+
+impl ForceVolume {
+    /// Acceleration at `position` for a volume with the given transform
+    pub fn acceleration_at(&self, transform: &GlobalTransform, position: Vec3) -> Vec3 {
+        let direction = transform.rotation() * self.direction.normalize_or_zero();
+        let strength = match self.range {
+            Some(range) => {
+                let distance = (position - transform.translation()).dot(direction);
+                1.0 - self
+                    .falloff
+                    .sample_clamped(distance / range.max(f32::EPSILON))
+            }
+            None => 1.0,
+        };
+        direction * self.acceleration * strength
+    }
+}
+
+#[allow(clippy::complexity)]
+pub(crate) fn apply_force_volumes(
+    volume_query: Query<(Entity, &ForceVolume, &Collider, &GlobalTransform)>,
+    mut puppet_query: Query<
+        (
+            &Transform,
+            &mut ExternalVelocity,
+            Has<Grounded>,
+            Option<&Puppeteer>,
+            &GravityScale,
+        ),
+        With<Puppet>,
+    >,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    for (entity, volume, collider, volume_transform) in &volume_query {
+        let (_, rotation, translation) = volume_transform.to_scale_rotation_translation();
+        for other in spatial_query.shape_intersections(
+            collider,
+            translation,
+            rotation,
+            &SpatialQueryFilter::default().with_excluded_entities([entity]),
+        ) {
+            let Ok((transform, mut external, grounded, puppeteer, gravity_scale)) =
+                puppet_query.get_mut(other)
+            else {
+                continue;
+            };
+
+            let mut acceleration = volume.acceleration_at(volume_transform, transform.translation);
+            if grounded {
+                // The ground already holds the puppet against gravity, so the vertical force
+                // would only pile up in the external velocity
+                let gravity = puppeteer.map_or(f32::INFINITY, |puppeteer| {
+                    puppeteer.gravity * gravity_scale.0
+                });
+                acceleration.x *= volume.grounded_multiplier;
+                acceleration.z *= volume.grounded_multiplier;
+                acceleration.y = (acceleration.y - gravity).max(0.0);
+            }
+            external.velocity += acceleration * time.delta_secs();
+        }
+    }
+}
@@ -1,17 +1,37 @@
 #![allow(dead_code)]
+pub mod avoidance;
+pub mod bounce;
+pub mod external;
+pub mod force;
+pub mod modifiers;
+pub mod navmesh;
+pub mod path;
 pub mod puppet;
 pub mod puppet_rig;
 pub mod puppeteer;
+pub mod push;
+pub mod trajectory;
 
 use avian3d::prelude::PhysicsSystems;
 use bevy::prelude::*;
 
+use avoidance::Avoidance;
+use modifiers::{ModifierOperation, ModifierStat, MovementModifier, MovementModifiers};
+use navmesh::NavMeshes;
+use path::{PathBlocked, PathCompleted, PuppeteerPath};
 use puppet::PuppetPlugin;
-use puppeteer::{Jumping, Puppeteer, PuppeteerInput};
+use puppeteer::{
+    FallDamage, FallDamageMultiplier, FallImpact, Falling, Gliding, JumpParameters, JumpProfile,
+    JumpTiming, Jumped, Jumping, MovementModel, Puppeteer, PuppeteerInput, QuakeMovement,
+    SpeedCurve, VelocityReset, WallSliding,
+};
+#[cfg(feature = "gizmos")]
+use trajectory::JumpPredictionGizmo;
 
 use crate::puppet_rig::PuppetRig;
 
 const MAX_BOUNCES: u32 = 5;
+const MAX_DEPENETRATION_ITERATIONS: u32 = 4;
 
 pub struct PuppeteerPlugin;
 
@@ -19,8 +39,31 @@ impl Plugin for PuppeteerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<Puppeteer>()
             .register_type::<PuppeteerInput>()
+            .register_type::<MovementModel>()
+            .register_type::<QuakeMovement>()
+            .register_type::<SpeedCurve>()
+            .register_type::<MovementModifiers>()
+            .register_type::<MovementModifier>()
+            .register_type::<ModifierStat>()
+            .register_type::<ModifierOperation>()
+            .register_type::<JumpTiming>()
+            .register_type::<JumpParameters>()
+            .register_type::<JumpProfile>()
+            .register_type::<VelocityReset>()
             .register_type::<Jumping>()
-            .register_type::<PuppetRig>();
+            .register_type::<Gliding>()
+            .register_type::<WallSliding>()
+            .register_type::<Falling>()
+            .register_type::<FallDamage>()
+            .register_type::<FallDamageMultiplier>()
+            .register_type::<PuppetRig>()
+            .register_type::<Avoidance>()
+            .register_type::<PuppeteerPath>();
+        app.add_message::<Jumped>()
+            .add_message::<FallImpact>()
+            .add_message::<PathCompleted>()
+            .add_message::<PathBlocked>();
+        app.init_resource::<NavMeshes>();
         app.add_plugins(PuppetPlugin);
         app.configure_sets(
             FixedPostUpdate,
@@ -36,10 +79,16 @@ impl Plugin for PuppeteerPlugin {
         app.add_systems(
             FixedPostUpdate,
             (
+                modifiers::update_movement_modifiers,
+                path::follow_path,
+                avoidance::avoid,
+                puppeteer::track_falls,
                 puppeteer::movement,
+                puppeteer::update_jump_parameters,
                 puppeteer::scale_gravity,
                 puppeteer::update_coyote_time,
                 puppeteer::update_jump_buffer,
+                puppeteer::drop_through,
                 puppeteer::jumping,
             )
                 .chain()
@@ -57,6 +106,15 @@ impl Plugin for PuppeteerPlugin {
                 .chain()
                 .in_set(PuppeteerSet::Move),
         );
+        #[cfg(feature = "gizmos")]
+        {
+            app.register_type::<JumpPredictionGizmo>();
+            app.add_systems(
+                Update,
+                trajectory::draw_jump_prediction
+                    .run_if(resource_exists::<bevy::gizmos::config::GizmoConfigStore>),
+            );
+        }
     }
 }
 /// System set for puppeteer systems.
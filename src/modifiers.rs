@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// A value of a [`Puppeteer`](crate::puppeteer::Puppeteer) that can be modified.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModifierStat {
+    /// Multiplies the top speed, `0.0` roots the puppeteer
+    MaxSpeed,
+    /// All acceleration, deceleration and turn speeds, but not the friction of
+    /// [`QuakeMovement`](crate::puppeteer::QuakeMovement)
+    Acceleration,
+    JumpHeight,
+    /// The [`GravityScale`](avian3d::prelude::GravityScale)
+    Gravity,
+}
+
+/// How a [`MovementModifier`] changes its stat.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub enum ModifierOperation {
+    Multiply(f32),
+    Add(f32),
+}
+
+/// A single modifier of a [`MovementModifiers`] stack.
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct MovementModifier {
+    pub stat: ModifierStat,
+    pub operation: ModifierOperation,
+    /// Removes the modifier when finished, `None` to keep it until it is removed
+    pub timer: Option<Timer>,
+}
+
+impl MovementModifier {
+    pub fn multiply(stat: ModifierStat, value: f32) -> Self {
+        Self {
+            stat,
+            operation: ModifierOperation::Multiply(value),
+            timer: None,
+        }
+    }
+
+    pub fn add(stat: ModifierStat, value: f32) -> Self {
+        Self {
+            stat,
+            operation: ModifierOperation::Add(value),
+            timer: None,
+        }
+    }
+
+    /// Removes the modifier after `duration`
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.timer = Some(Timer::new(duration, TimerMode::Once));
+        self
+    }
+}
+
+/// Speed, acceleration, jump and gravity modifiers from many sources, like slows, hastes or roots.
+///
+/// Modifiers are stored by key, inserting a modifier with an existing key replaces it.
+/// All additions of a stat are applied first and the result is multiplied by all multipliers,
+/// so the order modifiers are inserted in doesn't matter.
+#[derive(Reflect, Clone, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct MovementModifiers {
+    modifiers: Vec<(String, MovementModifier)>,
+}
+
+impl MovementModifiers {
+    /// Inserts a modifier, replacing the modifier with the same key
+    pub fn insert(&mut self, key: impl Into<String>, modifier: MovementModifier) {
+        let key = key.into();
+        if let Some((_, existing)) = self.modifiers.iter_mut().find(|(k, _)| *k == key) {
+            *existing = modifier;
+        } else {
+            self.modifiers.push((key, modifier));
+        }
+    }
+
+    /// Removes the modifier with the given key
+    pub fn remove(&mut self, key: &str) -> Option<MovementModifier> {
+        let index = self.modifiers.iter().position(|(k, _)| k == key)?;
+        Some(self.modifiers.remove(index).1)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&MovementModifier> {
+        self.modifiers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, modifier)| modifier)
+    }
+
+    pub fn clear(&mut self) {
+        self.modifiers.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &MovementModifier)> {
+        self.modifiers
+            .iter()
+            .map(|(key, modifier)| (key.as_str(), modifier))
+    }
+
+    /// Applies all modifiers of `stat` to `value`
+    pub fn apply(&self, stat: ModifierStat, value: f32) -> f32 {
+        let mut added = 0.0;
+        let mut multiplier = 1.0;
+        for (_, modifier) in self.modifiers.iter().filter(|(_, m)| m.stat == stat) {
+            match modifier.operation {
+                ModifierOperation::Multiply(value) => multiplier *= value,
+                ModifierOperation::Add(value) => added += value,
+            }
+        }
+        ((value + added) * multiplier).max(0.0)
+    }
+}
+
+pub fn update_movement_modifiers(mut query: Query<&mut MovementModifiers>, time: Res<Time>) {
+    for mut modifiers in &mut query {
+        if modifiers
+            .modifiers
+            .iter()
+            .all(|(_, modifier)| modifier.timer.is_none())
+        {
+            continue;
+        }
+
+        modifiers.modifiers.retain_mut(|(_, modifier)| {
+            modifier.timer.as_mut().is_none_or(|timer| {
+                timer.tick(time.delta());
+                !timer.is_finished()
+            })
+        });
+    }
+}
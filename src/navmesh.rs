@@ -0,0 +1,706 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use avian3d::prelude::{
+    Collider, ShapeCastConfig, SimpleCollider, SpatialQuery, SpatialQueryFilter,
+};
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    path::Waypoint,
+    puppet::{Puppet, SurfaceData, max_slope_angle},
+    puppeteer::Puppeteer,
+};
+
+/// Maximum number of floors baked on top of each other
+const MAX_LAYERS: usize = 8;
+/// Rises below this are flat ground rather than steps
+const STEP_TOLERANCE: f32 = 0.01;
+
+/// What an agent type can traverse, used to bake a [`NavMesh`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct NavAgent {
+    /// Horizontal radius of the collider
+    pub radius: f32,
+    /// Height of the collider
+    pub height: f32,
+    /// See [`Puppet::step_height`]
+    pub step_height: f32,
+    /// See [`Puppet::max_slope_angle`]
+    pub max_slope_angle: f32,
+    /// See [`Puppeteer::max_speed`]
+    pub max_speed: f32,
+    /// See [`Puppeteer::jump_height`], `0.0` if the agent can't jump
+    pub jump_height: f32,
+    /// See [`Puppeteer::time_to_jump_apex`]
+    pub time_to_jump_apex: f32,
+    /// See [`Puppeteer::fall_multiplier`]
+    pub downward_movement_multiplier: f32,
+}
+
+impl NavAgent {
+    /// Takes the capabilities of a puppet, optionally controlled by a puppeteer
+    pub fn new(puppet: &Puppet, puppeteer: Option<&Puppeteer>, collider: &Collider) -> Self {
+        let aabb = collider.aabb(Vec3::ZERO, Quat::IDENTITY);
+        let size = aabb.max - aabb.min;
+        Self {
+            radius: size.xz().max_element() * 0.5,
+            height: size.y,
+            step_height: puppet.step_height,
+            max_slope_angle: puppet.max_slope_angle,
+            max_speed: puppeteer.map_or(0.0, |puppeteer| puppeteer.max_speed),
+            jump_height: puppeteer.map_or(0.0, |puppeteer| puppeteer.jump_height),
+            time_to_jump_apex: puppeteer.map_or(0.0, |puppeteer| puppeteer.time_to_jump_apex()),
+            downward_movement_multiplier: puppeteer
+                .map_or(1.0, |puppeteer| puppeteer.fall_multiplier()),
+        }
+    }
+
+    /// Horizontal distance a jump can cover to land `height` above the start
+    pub fn jump_distance(&self, height: f32) -> f32 {
+        if self.jump_height <= 0.0 || self.time_to_jump_apex <= 0.0 || height > self.jump_height {
+            return 0.0;
+        }
+        let gravity = 2.0 * self.jump_height / (self.time_to_jump_apex * self.time_to_jump_apex);
+        let fall_gravity = gravity * self.downward_movement_multiplier.max(f32::EPSILON);
+        let fall_time = (2.0 * (self.jump_height - height) / fall_gravity).sqrt();
+        self.max_speed * (self.time_to_jump_apex + fall_time)
+    }
+
+    /// Height above the start of a jump after the given time
+    fn jump_height_at(&self, time: f32) -> f32 {
+        let gravity = 2.0 * self.jump_height / (self.time_to_jump_apex * self.time_to_jump_apex);
+        if time <= self.time_to_jump_apex {
+            return gravity * self.time_to_jump_apex * time - 0.5 * gravity * time * time;
+        }
+        let fall_gravity = gravity * self.downward_movement_multiplier.max(f32::EPSILON);
+        let fall_time = time - self.time_to_jump_apex;
+        self.jump_height - 0.5 * fall_gravity * fall_time * fall_time
+    }
+}
+
+/// Settings used to bake a [`NavMesh`].
+#[derive(Clone, Debug)]
+pub struct NavMeshSettings {
+    pub agent: NavAgent,
+    /// Minimum corner of the baked area
+    pub min: Vec3,
+    /// Maximum corner of the baked area
+    pub max: Vec3,
+    /// Horizontal size of a cell
+    pub cell_size: f32,
+    /// Colliders to bake. Sensors and puppets are always skipped, [`OneWayPlatform`]s are only
+    /// used as floor and [`NotWalkable`] colliders only as walls
+    ///
+    /// [`OneWayPlatform`]: crate::puppet::OneWayPlatform
+    /// [`NotWalkable`]: crate::puppet::NotWalkable
+    pub filter: SpatialQueryFilter,
+}
+
+/// A connection between two [`NavCell`]s.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NavLink {
+    /// Index of the connected cell
+    pub cell: usize,
+    /// Whether the agent has to jump to get to the cell
+    pub jump: bool,
+    pub cost: f32,
+}
+
+/// A walkable spot of a [`NavMesh`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavCell {
+    /// Position on the ground
+    pub position: Vec3,
+    pub links: Vec<NavLink>,
+}
+
+/// A navigation mesh made of a layered grid of walkable cells, baked for a single [`NavAgent`].
+///
+/// Bake one with [`NavMesh::bake`] for every agent type and keep them in [`NavMeshes`]. Paths
+/// found with [`NavMesh::find_path`] can be followed with a
+/// [`PuppeteerPath`](crate::path::PuppeteerPath).
+#[derive(Clone, Debug)]
+pub struct NavMesh {
+    /// The agent the nav mesh was baked for
+    pub agent: NavAgent,
+    pub cell_size: f32,
+    pub cells: Vec<NavCell>,
+    columns: HashMap<IVec2, Vec<usize>>,
+}
+
+/// The baked [`NavMesh`]es, one per [`NavAgent`]. Initialized by the
+/// [`PuppeteerPlugin`](crate::PuppeteerPlugin).
+#[derive(Resource, Clone, Debug, Default)]
+pub struct NavMeshes(Vec<NavMesh>);
+
+impl NavMeshes {
+    /// Adds a nav mesh, replacing the one baked for the same agent
+    pub fn insert(&mut self, nav_mesh: NavMesh) {
+        self.0.retain(|other| other.agent != nav_mesh.agent);
+        self.0.push(nav_mesh);
+    }
+
+    /// The nav mesh baked for the given agent
+    pub fn get(&self, agent: &NavAgent) -> Option<&NavMesh> {
+        self.0.iter().find(|nav_mesh| nav_mesh.agent == *agent)
+    }
+
+    /// Removes the nav mesh baked for the given agent
+    pub fn remove(&mut self, agent: &NavAgent) -> Option<NavMesh> {
+        let index = self
+            .0
+            .iter()
+            .position(|nav_mesh| nav_mesh.agent == *agent)?;
+        Some(self.0.swap_remove(index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NavMesh> {
+        self.0.iter()
+    }
+}
+
+#[derive(PartialEq)]
+struct OpenCell {
+    cost: f32,
+    cell: usize,
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NavMesh {
+    /// Bakes the walkable areas of the colliders in the given bounds
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`NavMeshSettings::cell_size`] isn't positive.
+    pub fn bake(
+        spatial_query: &SpatialQuery,
+        surface_query: &Query<SurfaceData>,
+        settings: &NavMeshSettings,
+    ) -> Self {
+        assert!(
+            settings.cell_size > 0.0,
+            "NavMeshSettings::cell_size must be positive, got {}",
+            settings.cell_size
+        );
+        let agent = &settings.agent;
+        let mut nav_mesh = Self::new(*agent, settings.cell_size);
+
+        // The agent shape above step height, everything below can be stepped over
+        let clearance_height = (agent.height - agent.step_height).max(agent.radius * 2.0);
+        let clearance = Collider::capsule(agent.radius, clearance_height - agent.radius * 2.0);
+        let clearance_offset = Vec3::Y * (agent.step_height + clearance_height * 0.5);
+        // The agent shape below step height, only blocked by colliders it can't step onto
+        let base_height = (agent.step_height - STEP_TOLERANCE).max(STEP_TOLERANCE);
+        let base = Collider::cylinder(agent.radius, base_height);
+        let base_offset = Vec3::Y * (STEP_TOLERANCE + base_height * 0.5);
+
+        let min = nav_mesh.column(settings.min);
+        let max = nav_mesh.column(settings.max);
+        for x in min.x..=max.x {
+            for z in min.y..=max.y {
+                let column = IVec2::new(x, z);
+                let center = nav_mesh.column_center(column);
+                let mut origin = Vec3::new(center.x, settings.max.y, center.y);
+
+                for _ in 0..MAX_LAYERS {
+                    let Some(hit) = spatial_query.cast_ray_predicate(
+                        origin,
+                        Dir3::NEG_Y,
+                        origin.y - settings.min.y,
+                        false,
+                        &settings.filter,
+                        &|entity| is_floor(entity, surface_query),
+                    ) else {
+                        break;
+                    };
+                    let position = origin - Vec3::Y * hit.distance;
+                    origin = position - Vec3::Y * 0.01;
+
+                    if Vec3::Y.angle_between(hit.normal).to_degrees()
+                        > max_slope_angle(agent.max_slope_angle, hit.entity, surface_query)
+                    {
+                        continue;
+                    }
+                    let mut clear = true;
+                    spatial_query.shape_intersections_callback(
+                        &clearance,
+                        position + clearance_offset,
+                        Quat::IDENTITY,
+                        &settings.filter,
+                        |entity| {
+                            clear = !blocks(entity, surface_query);
+                            clear
+                        },
+                    );
+                    spatial_query.shape_intersections_callback(
+                        &base,
+                        position + base_offset,
+                        Quat::IDENTITY,
+                        &settings.filter,
+                        |entity| {
+                            clear &= entity == hit.entity
+                                || !blocks(entity, surface_query)
+                                || is_steppable(entity, surface_query);
+                            clear
+                        },
+                    );
+                    if !clear {
+                        continue;
+                    }
+
+                    nav_mesh.insert_cell(position);
+                }
+            }
+        }
+
+        nav_mesh.link_cells(
+            spatial_query,
+            surface_query,
+            settings,
+            &clearance,
+            clearance_offset,
+        );
+        nav_mesh
+    }
+
+    fn link_cells(
+        &mut self,
+        spatial_query: &SpatialQuery,
+        surface_query: &Query<SurfaceData>,
+        settings: &NavMeshSettings,
+        clearance: &Collider,
+        clearance_offset: Vec3,
+    ) {
+        // Sweeps the agent between two points, ignoring colliders it passes through
+        let blocked = |from: Vec3, to: Vec3| {
+            Dir3::new(to - from).is_ok_and(|direction| {
+                spatial_query
+                    .cast_shape_predicate(
+                        clearance,
+                        from,
+                        Quat::IDENTITY,
+                        direction,
+                        &ShapeCastConfig::from_max_distance(from.distance(to)),
+                        &settings.filter,
+                        &|entity| blocks(entity, surface_query),
+                    )
+                    .is_some()
+            })
+        };
+
+        let agent = &settings.agent;
+        let max_rise = |length: f32, max_slope_angle: f32| {
+            agent
+                .step_height
+                .max(length * max_slope_angle.to_radians().tan().max(0.0))
+        };
+
+        // Whether the ground between two cells is a walkable slope without steps higher than
+        // the agent can step up, sampled every quarter of the way
+        let is_slope = |from: Vec3, to: Vec3| {
+            const SEGMENTS: u32 = 4;
+            let segment_length = from.xz().distance(to.xz()) / SEGMENTS as f32;
+            let top = from.y.max(to.y) + agent.step_height;
+            let mut previous = from.y;
+            let mut slope_limit = agent.max_slope_angle;
+            for index in 1..=SEGMENTS {
+                let height = if index == SEGMENTS {
+                    to.y
+                } else {
+                    let sample = from.lerp(to, index as f32 / SEGMENTS as f32).with_y(top);
+                    let Some(hit) = spatial_query.cast_ray_predicate(
+                        sample,
+                        Dir3::NEG_Y,
+                        top - from.y.min(to.y) + agent.step_height,
+                        false,
+                        &settings.filter,
+                        &|entity| is_floor(entity, surface_query),
+                    ) else {
+                        return false;
+                    };
+                    slope_limit = max_slope_angle(agent.max_slope_angle, hit.entity, surface_query);
+                    if Vec3::Y.angle_between(hit.normal).to_degrees() > slope_limit {
+                        return false;
+                    }
+                    top - hit.distance
+                };
+                if (height - previous).abs() > max_rise(segment_length, slope_limit) {
+                    return false;
+                }
+                previous = height;
+            }
+            true
+        };
+
+        // Whether the agent can step up onto the floor of a cell
+        let is_steppable_cell = |cell: Vec3| {
+            spatial_query
+                .cast_ray_predicate(
+                    cell + Vec3::Y * STEP_TOLERANCE,
+                    Dir3::NEG_Y,
+                    STEP_TOLERANCE * 2.0,
+                    false,
+                    &settings.filter,
+                    &|entity| is_floor(entity, surface_query),
+                )
+                .is_none_or(|hit| is_steppable(hit.entity, surface_query))
+        };
+
+        let jump_columns = (agent.jump_distance(0.0) / self.cell_size).ceil() as i32;
+
+        for index in 0..self.cells.len() {
+            let position = self.cells[index].position;
+            let column = self.column(position);
+            let mut links = Vec::new();
+
+            // Walking to neighbors and dropping off ledges
+            for offset in [-1, 0, 1]
+                .into_iter()
+                .flat_map(|x| [-1, 0, 1].map(|z| IVec2::new(x, z)))
+                .filter(|offset| *offset != IVec2::ZERO)
+            {
+                for &other in self.columns.get(&(column + offset)).into_iter().flatten() {
+                    let target = self.cells[other].position;
+                    let rise = target.y - position.y;
+                    // Only drop straight off ledges, rises are checked against the slope limit of
+                    // each surface by `is_slope`
+                    if rise < -max_rise((target - position).xz().length(), agent.max_slope_angle)
+                        && offset.length_squared() != 1
+                    {
+                        continue;
+                    }
+
+                    // Sweep the agent over the cell, so diagonal links don't cut wall corners.
+                    // Slopes are swept along the ground. Otherwise the agent moves at its height
+                    // until it is over the cell, then steps up or drops down.
+                    let from = position + clearance_offset;
+                    let link_blocked = if rise > agent.step_height {
+                        !is_slope(position, target) || blocked(from, target + clearance_offset)
+                    } else if rise > STEP_TOLERANCE && !is_steppable_cell(target) {
+                        true
+                    } else {
+                        let over = target.with_y(position.y) + clearance_offset;
+                        blocked(from, over)
+                            || blocked(
+                                over,
+                                target.with_y(target.y.max(position.y)) + clearance_offset,
+                            )
+                    };
+                    if !link_blocked {
+                        links.push(NavLink {
+                            cell: other,
+                            jump: false,
+                            cost: position.distance(target),
+                        });
+                    }
+                }
+            }
+
+            // Jumping over gaps and onto ledges, only from cells at an edge
+            if jump_columns > 1 && links.len() < 8 {
+                for x in -jump_columns..=jump_columns {
+                    for z in -jump_columns..=jump_columns {
+                        let offset = IVec2::new(x, z);
+                        if offset.abs().max_element() <= 1 {
+                            continue;
+                        }
+                        for &other in self.columns.get(&(column + offset)).into_iter().flatten() {
+                            let target = self.cells[other].position;
+                            let distance = (target - position).xz().length();
+                            if distance > agent.jump_distance(target.y - position.y) {
+                                continue;
+                            }
+
+                            // Sweep the agent along the arc of a jump at full speed, sampled
+                            // every half cell
+                            let samples = (distance / self.cell_size * 2.0).ceil().max(1.0);
+                            let sample = |index: f32| {
+                                let fraction = index / samples;
+                                let time = fraction * distance / agent.max_speed;
+                                position
+                                    .lerp(target, fraction)
+                                    .with_y(position.y + agent.jump_height_at(time))
+                                    + clearance_offset
+                            };
+                            let arc_blocked = (0..samples as u32).any(|index| {
+                                let index = index as f32;
+                                blocked(sample(index), sample(index + 1.0))
+                            });
+                            if !arc_blocked {
+                                links.push(NavLink {
+                                    cell: other,
+                                    jump: true,
+                                    cost: position.distance(target) * 2.0,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+
+            self.cells[index].links = links;
+        }
+    }
+
+    fn new(agent: NavAgent, cell_size: f32) -> Self {
+        Self {
+            agent,
+            cell_size,
+            cells: Vec::new(),
+            columns: HashMap::default(),
+        }
+    }
+
+    /// Adds an unlinked cell and returns its index
+    fn insert_cell(&mut self, position: Vec3) -> usize {
+        let index = self.cells.len();
+        self.columns
+            .entry(self.column(position))
+            .or_default()
+            .push(index);
+        self.cells.push(NavCell {
+            position,
+            links: Vec::new(),
+        });
+        index
+    }
+
+    fn column(&self, position: Vec3) -> IVec2 {
+        (position.xz() / self.cell_size).floor().as_ivec2()
+    }
+
+    fn column_center(&self, column: IVec2) -> Vec2 {
+        (column.as_vec2() + 0.5) * self.cell_size
+    }
+
+    /// The cell closest to the given position, preferring cells below it
+    pub fn nearest_cell(&self, position: Vec3) -> Option<usize> {
+        let column = self.column(position);
+        (0..=2)
+            .flat_map(|distance| {
+                (-distance..=distance).flat_map(move |x| {
+                    (-distance..=distance).map(move |z| column + IVec2::new(x, z))
+                })
+            })
+            .filter_map(|column| self.columns.get(&column))
+            .flatten()
+            .copied()
+            .min_by(|&a, &b| {
+                let cost = |cell: usize| {
+                    let offset = self.cells[cell].position - position;
+                    // Cells above the position are likely on a ceiling or another floor
+                    offset.length() + offset.y.max(0.0) * 4.0
+                };
+                cost(a).total_cmp(&cost(b))
+            })
+    }
+
+    /// Finds a path between the cells closest to `start` and `end` using A*.
+    ///
+    /// Waypoints that have to be jumped from are marked with [`Waypoint::jump`].
+    pub fn find_path(&self, start: Vec3, end: Vec3) -> Option<Vec<Waypoint>> {
+        let start = self.nearest_cell(start)?;
+        let end = self.nearest_cell(end)?;
+        let end_position = self.cells[end].position;
+
+        let mut costs = HashMap::<usize, f32>::default();
+        let mut previous = HashMap::<usize, (usize, bool)>::default();
+        let mut open = BinaryHeap::new();
+        costs.insert(start, 0.0);
+        open.push(OpenCell {
+            cost: self.cells[start].position.distance(end_position),
+            cell: start,
+        });
+
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            if cell == end {
+                return Some(self.build_path(start, end, &previous));
+            }
+            let cost = costs[&cell];
+            for link in &self.cells[cell].links {
+                let link_cost = cost + link.cost;
+                if costs
+                    .get(&link.cell)
+                    .is_some_and(|&known| known <= link_cost)
+                {
+                    continue;
+                }
+                costs.insert(link.cell, link_cost);
+                previous.insert(link.cell, (cell, link.jump));
+                open.push(OpenCell {
+                    cost: link_cost + self.cells[link.cell].position.distance(end_position),
+                    cell: link.cell,
+                });
+            }
+        }
+        None
+    }
+
+    fn build_path(
+        &self,
+        start: usize,
+        end: usize,
+        previous: &HashMap<usize, (usize, bool)>,
+    ) -> Vec<Waypoint> {
+        let mut waypoints = vec![Waypoint::new(self.cells[end].position)];
+        let mut cell = end;
+        while cell != start {
+            let (from, jump) = previous[&cell];
+            let position = self.cells[from].position;
+            waypoints.push(if jump {
+                Waypoint::jump(position)
+            } else {
+                Waypoint::new(position)
+            });
+            cell = from;
+        }
+        waypoints.reverse();
+
+        // Drop waypoints on a straight line between their neighbors
+        let mut simplified: Vec<Waypoint> = Vec::with_capacity(waypoints.len());
+        for (index, waypoint) in waypoints.iter().enumerate() {
+            if let (Some(last), Some(next)) = (simplified.last(), waypoints.get(index + 1))
+                && !waypoint.jump
+                && (waypoint.position - last.position)
+                    .normalize_or_zero()
+                    .dot((next.position - waypoint.position).normalize_or_zero())
+                    > 0.999
+            {
+                continue;
+            }
+            simplified.push(*waypoint);
+        }
+        simplified
+    }
+}
+
+/// Whether the agent can stand on the collider
+fn is_floor(entity: Entity, surface_query: &Query<SurfaceData>) -> bool {
+    surface_query
+        .get(entity)
+        .is_ok_and(|surface| !surface.sensor && !surface.puppet && !surface.not_walkable)
+}
+
+/// Whether the agent can step onto the collider, puppets don't step onto [`NoStep`] and
+/// [`NotWalkable`] colliders
+///
+/// [`NoStep`]: crate::puppet::NoStep
+/// [`NotWalkable`]: crate::puppet::NotWalkable
+fn is_steppable(entity: Entity, surface_query: &Query<SurfaceData>) -> bool {
+    surface_query
+        .get(entity)
+        .is_ok_and(|surface| !surface.no_step && !surface.not_walkable)
+}
+
+/// Whether the collider blocks the agent while moving.
+/// One-way platforms can be passed from below and from the sides, so they are only floor.
+fn blocks(entity: Entity, surface_query: &Query<SurfaceData>) -> bool {
+    surface_query
+        .get(entity)
+        .is_ok_and(|surface| !surface.sensor && !surface.puppet && !surface.one_way)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The agent of a default puppeteer, the hand built nav meshes don't depend on it
+    fn agent() -> NavAgent {
+        NavAgent::new(
+            &Puppet::default(),
+            Some(&Puppeteer::default()),
+            &Collider::capsule(0.25, 1.2),
+        )
+    }
+
+    fn link(nav_mesh: &mut NavMesh, from: usize, to: usize, jump: bool) {
+        let cost = nav_mesh.cells[from]
+            .position
+            .distance(nav_mesh.cells[to].position);
+        nav_mesh.cells[from].links.push(NavLink {
+            cell: to,
+            jump,
+            cost,
+        });
+    }
+
+    /// A straight walkway with a gap that has to be jumped, followed by a turn
+    fn walkway() -> NavMesh {
+        let mut nav_mesh = NavMesh::new(agent(), 1.0);
+        let cells = [
+            Vec3::new(0.5, 0.0, 0.5),
+            Vec3::new(1.5, 0.0, 0.5),
+            Vec3::new(2.5, 0.0, 0.5),
+            Vec3::new(3.5, 0.0, 0.5),
+            Vec3::new(5.5, 0.0, 0.5),
+            Vec3::new(5.5, 0.0, 1.5),
+        ]
+        .map(|position| nav_mesh.insert_cell(position));
+        for pair in [[0, 1], [1, 2], [2, 3], [4, 5]] {
+            link(&mut nav_mesh, cells[pair[0]], cells[pair[1]], false);
+            link(&mut nav_mesh, cells[pair[1]], cells[pair[0]], false);
+        }
+        link(&mut nav_mesh, cells[3], cells[4], true);
+        nav_mesh
+    }
+
+    #[test]
+    fn path_jumps_the_gap_and_drops_straight_waypoints() {
+        let path = walkway()
+            .find_path(Vec3::new(0.4, 0.5, 0.6), Vec3::new(5.6, 0.5, 1.4))
+            .expect("the end is reachable");
+        assert_eq!(
+            path,
+            vec![
+                Waypoint::new(Vec3::new(0.5, 0.0, 0.5)),
+                Waypoint::jump(Vec3::new(3.5, 0.0, 0.5)),
+                Waypoint::new(Vec3::new(5.5, 0.0, 0.5)),
+                Waypoint::new(Vec3::new(5.5, 0.0, 1.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn jump_links_are_one_way() {
+        assert_eq!(
+            walkway().find_path(Vec3::new(5.5, 0.0, 1.5), Vec3::new(0.5, 0.0, 0.5)),
+            None
+        );
+    }
+
+    #[test]
+    fn nav_meshes_are_kept_per_agent() {
+        let small = walkway();
+        let mut large = NavMesh::new(
+            NavAgent {
+                radius: 1.0,
+                ..agent()
+            },
+            1.0,
+        );
+        large.insert_cell(Vec3::ZERO);
+
+        let mut nav_meshes = NavMeshes::default();
+        nav_meshes.insert(small.clone());
+        nav_meshes.insert(large.clone());
+        assert_eq!(nav_meshes.get(&small.agent).unwrap().cells, small.cells);
+        assert_eq!(nav_meshes.get(&large.agent).unwrap().cells, large.cells);
+
+        nav_meshes.insert(NavMesh::new(agent(), 1.0));
+        assert_eq!(nav_meshes.iter().count(), 2);
+        assert!(nav_meshes.get(&agent()).unwrap().cells.is_empty());
+    }
+}
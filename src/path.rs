@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{puppet::Puppet, puppeteer::PuppeteerInput};
+
+/// A point on a [`PuppeteerPath`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct Waypoint {
+    pub position: Vec3,
+    /// Jump when this waypoint is reached
+    pub jump: bool,
+}
+
+impl Waypoint {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            jump: false,
+        }
+    }
+
+    /// A waypoint where the puppeteer jumps towards the next waypoint
+    pub fn jump(position: Vec3) -> Self {
+        Self {
+            position,
+            jump: true,
+        }
+    }
+}
+
+/// Add this component to a [`Puppeteer`](crate::puppeteer::Puppeteer) to follow a path.
+///
+/// The path is turned into [`PuppeteerInput`] every tick and removed once the last waypoint
+/// is reached. While following the path, [`Self::speed_multiplier`] is used instead of
+/// [`PuppeteerInput::speed_multiplier`], which is left untouched.
+#[derive(Reflect, Clone, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct PuppeteerPath {
+    pub waypoints: Vec<Waypoint>,
+    /// Index of the waypoint the puppeteer is moving towards
+    pub current: usize,
+    /// Horizontal distance at which a waypoint counts as reached
+    pub arrival_distance: f32,
+    /// Distance to the last waypoint at which the puppeteer starts slowing down
+    pub slowing_distance: f32,
+    pub speed_multiplier: f32,
+    /// Time without getting closer to the current waypoint until the path counts as blocked
+    pub blocked_time: Duration,
+
+    closest_distance: f32,
+    stalled: Duration,
+    jumping: bool,
+}
+
+impl PuppeteerPath {
+    pub fn new(waypoints: impl IntoIterator<Item = Waypoint>) -> Self {
+        Self {
+            waypoints: waypoints.into_iter().collect(),
+            ..default()
+        }
+    }
+
+    /// A path through the given points without jumps
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        Self::new(points.into_iter().map(Waypoint::new))
+    }
+
+    /// Speed multiplier at `distance` to the last waypoint
+    fn arrival_speed_multiplier(&self, distance: f32) -> f32 {
+        if self.slowing_distance > 0.0 {
+            self.speed_multiplier * (distance / self.slowing_distance).min(1.0)
+        } else {
+            self.speed_multiplier
+        }
+    }
+}
+
+impl Default for PuppeteerPath {
+    fn default() -> Self {
+        Self {
+            waypoints: Vec::new(),
+            current: 0,
+            arrival_distance: 0.3,
+            slowing_distance: 1.5,
+            speed_multiplier: 1.0,
+            blocked_time: Duration::from_secs(2),
+            closest_distance: f32::INFINITY,
+            stalled: Duration::ZERO,
+            jumping: false,
+        }
+    }
+}
+
+/// Message sent when a puppeteer reached the last waypoint of its [`PuppeteerPath`].
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct PathCompleted {
+    pub entity: Entity,
+}
+
+/// Message sent when a puppeteer didn't get closer to its current waypoint for
+/// [`PuppeteerPath::blocked_time`].
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct PathBlocked {
+    pub entity: Entity,
+    /// Index of the waypoint that couldn't be reached
+    pub waypoint: usize,
+}
+
+pub fn follow_path(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut PuppeteerPath,
+        &mut PuppeteerInput,
+        &Puppet,
+        &Transform,
+    )>,
+    mut completed: MessageWriter<PathCompleted>,
+    mut blocked: MessageWriter<PathBlocked>,
+    time: Res<Time>,
+) {
+    for (entity, mut path, mut input, puppet, transform) in &mut query {
+        // Hold the jump until the apex for the full jump height
+        if path.jumping && puppet.gravity_velocity <= 0.0 {
+            path.jumping = false;
+            input.stop_jump();
+        }
+
+        let position = transform.translation.xz();
+        while let Some(waypoint) = path.waypoints.get(path.current).copied() {
+            if waypoint.position.xz().distance(position) > path.arrival_distance {
+                break;
+            }
+            if waypoint.jump {
+                path.jumping = true;
+                input.start_jump();
+            }
+            path.current += 1;
+            path.closest_distance = f32::INFINITY;
+            path.stalled = Duration::ZERO;
+        }
+
+        let Some(waypoint) = path.waypoints.get(path.current).copied() else {
+            // Finish a jump at the end of the path before completing it, otherwise the jump is
+            // never released
+            if path.jumping
+                && let Some(last) = path.waypoints.last()
+            {
+                let offset = last.position.xz() - position;
+                let direction = offset.normalize_or_zero();
+                input.move_amount(Vec3::new(direction.x, 0.0, direction.y));
+                input.path_multiplier = Some(path.arrival_speed_multiplier(offset.length()));
+                continue;
+            }
+            input.move_amount(Vec3::ZERO);
+            commands.entity(entity).remove::<PuppeteerPath>();
+            completed.write(PathCompleted { entity });
+            continue;
+        };
+
+        let offset = waypoint.position.xz() - position;
+        let distance = offset.length();
+        if distance < path.closest_distance - 0.01 {
+            path.closest_distance = distance;
+            path.stalled = Duration::ZERO;
+        } else {
+            path.stalled += time.delta();
+            if path.stalled >= path.blocked_time {
+                path.stalled = Duration::ZERO;
+                blocked.write(PathBlocked {
+                    entity,
+                    waypoint: path.current,
+                });
+            }
+        }
+
+        let speed_multiplier = if path.current == path.waypoints.len() - 1 {
+            path.arrival_speed_multiplier(distance)
+        } else {
+            path.speed_multiplier
+        };
+
+        let direction = offset.normalize_or_zero();
+        input.move_amount(Vec3::new(direction.x, 0.0, direction.y));
+        input.path_multiplier = Some(speed_multiplier);
+    }
+}
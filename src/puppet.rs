@@ -1,22 +1,65 @@
-use avian3d::prelude::{
-    Collider, ColliderMassProperties, Forces, GravityScale, RigidBody, RigidBodyForces,
-    ShapeCastConfig, SpatialQuery, SpatialQueryFilter,
+use avian3d::{
+    collision::collider::contact_query,
+    prelude::{
+        AngularVelocity, Collider, ColliderAabb, ColliderOf, ComputedCenterOfMass, GravityScale,
+        LinearVelocity, PhysicsSystems, Position, RigidBody, Rotation, Sensor, ShapeCastConfig,
+        ShapeHitData, SpatialQuery, SpatialQueryFilter,
+    },
 };
-use bevy::prelude::*;
+use bevy::{ecs::query::QueryData, prelude::*};
 
-use crate::{MAX_BOUNCES, PuppeteerSet, puppeteer::GravityMultiplier};
+use crate::{
+    MAX_BOUNCES, MAX_DEPENETRATION_ITERATIONS, PuppeteerSet,
+    bounce::{self, BouncePad, Bouncing},
+    external::{self, ExternalVelocity, Launched},
+    force::{self, ForceVolume},
+    puppeteer::GravityMultiplier,
+    push::{self, NotPushable, PuppetCrushed, Push, PushParams, PushSettings, SeparationSettings},
+};
+
+/// Seconds after which a [`DropThrough`] that never reached its platform is given up
+const DROP_THROUGH_TIMEOUT: f32 = 0.5;
 
 pub struct PuppetPlugin;
 impl Plugin for PuppetPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Puppet>();
+        app.add_message::<PuppetCrushed>()
+            .add_message::<PuppetStuck>();
+        app.register_type::<Puppet>()
+            .register_type::<SurfaceVelocity>()
+            .register_type::<PuppetSurface>()
+            .register_type::<NoStep>()
+            .register_type::<NotWalkable>()
+            .register_type::<OneWayPlatform>()
+            .register_type::<DropThrough>()
+            .register_type::<PushSettings>()
+            .register_type::<NotPushable>()
+            .register_type::<SeparationSettings>()
+            .register_type::<BouncePad>()
+            .register_type::<Bouncing>()
+            .register_type::<Launched>()
+            .register_type::<ExternalVelocity>()
+            .register_type::<ForceVolume>()
+            .register_type::<SlopeSpeed>();
         app.add_systems(
             FixedPostUpdate,
             (
-                check_if_grounded.in_set(PuppeteerSet::Prepare),
-                (handle_moving_platforms, move_puppet)
+                (
+                    push::push_puppets,
+                    update_drop_through,
+                    push::separate_puppets,
+                    depenetrate,
+                    check_if_grounded,
+                    bounce::bounce,
+                    force::apply_force_volumes,
+                    external::update_external_velocity,
+                )
+                    .chain()
+                    .in_set(PuppeteerSet::Prepare),
+                (handle_moving_platforms, move_puppet, push::apply_weight)
                     .chain()
                     .in_set(PuppeteerSet::Move),
+                carry_vertical_platforms.after(PhysicsSystems::Writeback),
             ),
         );
     }
@@ -35,7 +78,10 @@ impl Plugin for PuppetPlugin {
     Transform,
     GravityScale,
     GravityMultiplier,
-    HandleMovingPlatforms
+    HandleMovingPlatforms,
+    PushSettings,
+    SeparationSettings,
+    ExternalVelocity
 )]
 pub struct Puppet {
     /// The amount of extra distance added to collision checks
@@ -74,6 +120,16 @@ pub struct Puppet {
     /// The current gravity velocity.
     /// This **doesn't** get reset
     pub gravity_velocity: f32,
+
+    /// How strongly the puppet resists being pushed by dynamic rigid bodies
+    pub mass: f32,
+
+    /// The maximum downward [`gravity_velocity`](Self::gravity_velocity), `None` for no limit.
+    /// A [`Puppeteer`](crate::puppeteer::Puppeteer) has its own limits.
+    pub max_fall_speed: Option<f32>,
+
+    /// How walking up or down slopes changes the speed of the puppet
+    pub slope_speed: SlopeSpeed,
 }
 
 impl Puppet {
@@ -94,39 +150,91 @@ impl Default for Puppet {
             target_velocity: Vec3::ZERO,
             gravity_velocity: 0.0,
             target_position: Vec3::ZERO,
+            mass: 70.0,
+            max_fall_speed: None,
+            slope_speed: SlopeSpeed::default(),
+        }
+    }
+}
+
+/// How walking on slopes changes the speed of a [`Puppet`].
+///
+/// By default the puppet moves as fast along a slope as on flat ground.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct SlopeSpeed {
+    /// Speed multiplier when walking up a slope of [`Puppet::max_slope_angle`]
+    pub uphill_multiplier: f32,
+    /// Speed multiplier when walking down a slope of [`Puppet::max_slope_angle`]
+    pub downhill_multiplier: f32,
+    /// How the multipliers change from flat ground to the steepest slope
+    pub curve: EaseFunction,
+    /// Keeps the horizontal speed on slopes instead of the speed along the slope
+    pub preserve_horizontal_speed: bool,
+}
+
+impl Default for SlopeSpeed {
+    fn default() -> Self {
+        Self {
+            uphill_multiplier: 1.0,
+            downhill_multiplier: 1.0,
+            curve: EaseFunction::Linear,
+            preserve_horizontal_speed: false,
         }
     }
 }
 
+impl SlopeSpeed {
+    /// The speed multiplier when moving in `direction` on ground with the given normal
+    pub fn multiplier(&self, normal: Vec3, direction: Vec3, max_slope_angle: f32) -> f32 {
+        let angle = normal.angle_between(Vec3::Y).to_degrees();
+        let end = if direction.xz().dot(normal.xz()) < 0.0 {
+            self.uphill_multiplier
+        } else {
+            self.downhill_multiplier
+        };
+        EasingCurve::new(1.0, end, self.curve)
+            .sample_clamped(angle / max_slope_angle.max(f32::EPSILON))
+    }
+}
+
 /// Marker component for a puppet that is currently grounded and
 /// the entity the puppet is standing on.
 #[derive(Clone, Debug, PartialEq, Copy, Component)]
 #[component(storage = "SparseSet")]
 pub struct Grounded(pub Entity);
 
+#[allow(clippy::complexity)]
 pub(crate) fn check_if_grounded(
     mut commands: Commands,
     mut controller_query: Query<(
         &Puppet,
-        &mut Transform,
+        &Transform,
         &Collider,
         Entity,
         Option<&mut Grounded>,
+        Option<&DropThrough>,
     )>,
     spatial_query: SpatialQuery,
+    surface_query: Query<SurfaceData>,
 ) {
-    for (controller, mut transform, collider, entity, grounded) in controller_query.iter_mut() {
-        if let Some(hit) = spatial_query.cast_shape(
+    for (controller, transform, collider, entity, grounded, drop_through) in
+        controller_query.iter_mut()
+    {
+        let hit = cast_puppet_shape(
+            &spatial_query,
             collider,
             transform.translation,
-            Quat::default(),
             Dir3::NEG_Y,
-            &ShapeCastConfig::from_max_distance(controller.skin_thickness * 2.0),
-            &SpatialQueryFilter::default().with_excluded_entities([entity]),
-        ) {
-            if hit.distance == 0.0 {
-                transform.translation.y += controller.skin_thickness;
-            }
+            controller.skin_thickness * 2.0,
+            puppet_filter(entity, drop_through),
+            &surface_query,
+        );
+
+        if let Some(hit) = hit
+            && surface_query
+                .get(hit.entity)
+                .is_ok_and(|surface| !surface.not_walkable && !surface.puppet)
+        {
             if let Some(mut grounded) = grounded {
                 if grounded.0 != hit.entity {
                     grounded.0 = hit.entity;
@@ -140,10 +248,381 @@ pub(crate) fn check_if_grounded(
     }
 }
 
+/// Message sent when a puppet overlaps geometry that it can't be moved out of.
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct PuppetStuck {
+    /// The stuck puppet
+    pub puppet: Entity,
+    /// A collider the puppet still overlaps
+    pub collider: Entity,
+}
+
+/// Moves puppets out of any geometry they overlap, for example after spawning inside a wall,
+/// teleporting or changing the collider size.
+fn depenetrate(
+    mut puppet_query: Query<(
+        Entity,
+        &Puppet,
+        &Collider,
+        &mut Transform,
+        Option<&DropThrough>,
+    )>,
+    collider_query: Query<(&Collider, &Position, &Rotation)>,
+    spatial_query: SpatialQuery,
+    surface_query: Query<SurfaceData>,
+    mut stuck: MessageWriter<PuppetStuck>,
+) {
+    // Overlapping one-way platforms, sensors and other puppets is fine, the puppet passes through them
+    let is_solid = |collider: Entity| {
+        surface_query
+            .get(collider)
+            .is_ok_and(|surface| !surface.one_way && !surface.puppet && !surface.sensor)
+    };
+
+    for (entity, puppet, collider, mut transform, drop_through) in puppet_query.iter_mut() {
+        let query_filter = puppet_filter(entity, drop_through);
+
+        for _ in 0..MAX_DEPENETRATION_ITERATIONS {
+            let mut moved = false;
+            for other in spatial_query.shape_intersections(
+                collider,
+                transform.translation,
+                Quat::default(),
+                &query_filter,
+            ) {
+                if !is_solid(other) {
+                    continue;
+                }
+                let Ok((other_collider, other_position, other_rotation)) =
+                    collider_query.get(other)
+                else {
+                    continue;
+                };
+                let Ok(Some(contact)) = contact_query::contact(
+                    collider,
+                    transform.translation,
+                    Rotation::default(),
+                    other_collider,
+                    *other_position,
+                    *other_rotation,
+                    0.0,
+                ) else {
+                    continue;
+                };
+                if contact.penetration <= 0.0 {
+                    continue;
+                }
+
+                // The normal points from the puppet towards the other collider
+                transform.translation -= contact.global_normal1(&Rotation::default())
+                    * (contact.penetration + puppet.skin_thickness);
+                moved = true;
+            }
+            if !moved {
+                break;
+            }
+        }
+
+        if let Some(collider) = spatial_query
+            .shape_intersections(
+                collider,
+                transform.translation,
+                Quat::default(),
+                &query_filter,
+            )
+            .into_iter()
+            .find(|&collider| is_solid(collider))
+        {
+            stuck.write(PuppetStuck {
+                puppet: entity,
+                collider,
+            });
+        }
+    }
+}
+
+/// Velocity in local space added to any puppet standing on this collider,
+/// without the collider having to move itself.
+///
+/// Useful for conveyor belts, escalators or river rafts.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq, Deref, DerefMut)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct SurfaceVelocity(pub Vec3);
+
+/// Surface properties of a collider that change how puppets move on it.
+///
+/// Used for floors like ice, mud or sand.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct PuppetSurface {
+    /// Multiplies the acceleration and turn speed of a [`Puppeteer`](crate::puppeteer::Puppeteer) on this surface
+    pub acceleration_multiplier: f32,
+    /// Multiplies the deceleration of a [`Puppeteer`](crate::puppeteer::Puppeteer) on this surface
+    pub deceleration_multiplier: f32,
+    /// Multiplies the max speed of a [`Puppeteer`](crate::puppeteer::Puppeteer) on this surface
+    pub max_speed_multiplier: f32,
+    /// Overrides [`Puppet::max_slope_angle`] for this surface
+    pub max_slope_angle: Option<f32>,
+    /// User defined tag, for example to pick footstep sounds
+    pub tag: u32,
+}
+
+impl Default for PuppetSurface {
+    fn default() -> Self {
+        Self {
+            acceleration_multiplier: 1.0,
+            deceleration_multiplier: 1.0,
+            max_speed_multiplier: 1.0,
+            max_slope_angle: None,
+            tag: 0,
+        }
+    }
+}
+
+/// Marker component for colliders that puppets can't step onto.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct NoStep;
+
+/// Marker component for colliders that puppets can't stand on, regardless of their slope.
+/// Puppets slowly slide off them instead and don't build up fall speed on top of them.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct NotWalkable;
+
+/// A platform that puppets can pass through from below and from the sides,
+/// but which catches them from above.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct OneWayPlatform;
+
+/// Add this component to a puppet to let it drop through a [`OneWayPlatform`].
+/// Removed once the puppet has passed the platform, landed on something else, got back above
+/// the platform while rising, or didn't reach the platform in time.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, PartialEq)]
+#[component(storage = "SparseSet")]
+pub struct DropThrough {
+    /// The platform to drop through
+    pub platform: Entity,
+    entered: bool,
+    elapsed: f32,
+}
+
+impl DropThrough {
+    pub fn new(platform: Entity) -> Self {
+        Self {
+            platform,
+            entered: false,
+            elapsed: 0.0,
+        }
+    }
+}
+
+/// Surface related components of a collider hit by a puppet.
+#[derive(QueryData)]
+pub struct SurfaceData {
+    surface: Option<&'static PuppetSurface>,
+    pub(crate) no_step: Has<NoStep>,
+    pub(crate) not_walkable: Has<NotWalkable>,
+    pub(crate) one_way: Has<OneWayPlatform>,
+    pub(crate) puppet: Has<Puppet>,
+    pub(crate) sensor: Has<Sensor>,
+}
+
+/// Whether the puppet passes through the hit collider when moving in the given direction.
+///
+/// Sensors never block the puppet.
+/// One-way platforms only block moves that don't go up and hit their top surface, so the puppet can
+/// still walk up a sloped one.
+/// Other puppets are solid unless moving up or already overlapping them, overlaps are resolved by
+/// [`SeparationSettings`] instead.
+fn passes_through(hit: &ShapeHitData, direction: Vec3, surface_query: &Query<SurfaceData>) -> bool {
+    let Ok(surface) = surface_query.get(hit.entity) else {
+        return false;
+    };
+
+    surface.sensor
+        || (surface.one_way && (hit.distance <= 0.0 || direction.y > 0.0 || hit.normal1.y <= 0.0))
+        || (surface.puppet && (hit.distance <= 0.0 || direction.y > 0.0))
+}
+
+/// Casts the collider of a puppet, ignoring colliders the puppet passes through
+pub(crate) fn cast_puppet_shape(
+    spatial_query: &SpatialQuery,
+    collider: &Collider,
+    origin: Vec3,
+    direction: Dir3,
+    max_distance: f32,
+    mut query_filter: SpatialQueryFilter,
+    surface_query: &Query<SurfaceData>,
+) -> Option<ShapeHitData> {
+    loop {
+        let hit = spatial_query.cast_shape(
+            collider,
+            origin,
+            Quat::default(),
+            direction,
+            &ShapeCastConfig::from_max_distance(max_distance),
+            &query_filter,
+        );
+        match hit {
+            Some(hit) if passes_through(&hit, *direction, surface_query) => {
+                query_filter.excluded_entities.insert(hit.entity);
+            }
+            _ => return hit,
+        }
+    }
+}
+
+/// Spatial query filter used for the collision checks of a puppet
+pub(crate) fn puppet_filter(
+    entity: Entity,
+    drop_through: Option<&DropThrough>,
+) -> SpatialQueryFilter {
+    let mut query_filter = SpatialQueryFilter::default().with_excluded_entities([entity]);
+    if let Some(drop_through) = drop_through {
+        query_filter.excluded_entities.insert(drop_through.platform);
+    }
+    query_filter
+}
+
+#[allow(clippy::complexity)]
+fn update_drop_through(
+    mut commands: Commands,
+    mut puppet_query: Query<(
+        Entity,
+        &Puppet,
+        Option<&Grounded>,
+        &Collider,
+        &ColliderAabb,
+        &Transform,
+        &mut DropThrough,
+    )>,
+    platform_query: Query<&ColliderAabb, Without<Puppet>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    for (entity, puppet, grounded, collider, aabb, transform, mut drop_through) in
+        puppet_query.iter_mut()
+    {
+        drop_through.elapsed += time.delta_secs();
+        let intersecting = spatial_query
+            .shape_intersections(
+                collider,
+                transform.translation,
+                Quat::default(),
+                &SpatialQueryFilter::default().with_excluded_entities([entity]),
+            )
+            .contains(&drop_through.platform);
+
+        if intersecting {
+            drop_through.entered = true;
+            continue;
+        }
+
+        let grounded_elsewhere =
+            grounded.is_some_and(|grounded| grounded.0 != drop_through.platform);
+        let rising_above = puppet.gravity_velocity > 0.0
+            && platform_query
+                .get(drop_through.platform)
+                .is_ok_and(|platform| aabb.min.y >= platform.max.y);
+        if drop_through.entered
+            || grounded_elsewhere
+            || rising_above
+            || drop_through.elapsed >= DROP_THROUGH_TIMEOUT
+        {
+            commands.entity(entity).remove::<DropThrough>();
+        }
+    }
+}
+
+/// Whether the puppet can stand on the given surface with the given surface normal
+fn is_walkable(
+    puppet: &Puppet,
+    surface: Entity,
+    normal: Vec3,
+    surface_query: &Query<SurfaceData>,
+) -> bool {
+    let angle = Vec3::Y.angle_between(normal).to_degrees();
+    let max_slope_angle = max_slope_angle(puppet.max_slope_angle, surface, surface_query);
+    let Ok(surface) = surface_query.get(surface) else {
+        return angle <= max_slope_angle;
+    };
+
+    !surface.not_walkable && !surface.puppet && angle <= max_slope_angle
+}
+
+/// The max slope angle on the given surface: its [`PuppetSurface`] override or the given default
+pub(crate) fn max_slope_angle(
+    default: f32,
+    surface: Entity,
+    surface_query: &Query<SurfaceData>,
+) -> f32 {
+    surface_query
+        .get(surface)
+        .ok()
+        .and_then(|surface| surface.surface)
+        .and_then(|surface| surface.max_slope_angle)
+        .unwrap_or(default)
+}
+
+/// Whether the puppet can step onto the given surface
+fn is_steppable(surface: Entity, surface_query: &Query<SurfaceData>) -> bool {
+    surface_query
+        .get(surface)
+        .is_ok_and(|surface| !surface.no_step && !surface.not_walkable && !surface.puppet)
+}
+
 /// Add this component to a puppet to handle moving platforms.
-#[derive(Debug, Clone, Copy, PartialEq, Component, Default, Deref)]
+#[derive(Debug, Clone, Copy, PartialEq, Component, Default)]
 struct HandleMovingPlatforms {
     pub previous_global_transform: Option<GlobalTransform>,
+    /// The platform and its position and rotation after the last physics step
+    previous_platform_pose: Option<(Entity, Vec3, Quat)>,
+}
+
+pub(crate) type PlatformBodyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RigidBody,
+        &'static LinearVelocity,
+        &'static AngularVelocity,
+        &'static Position,
+        &'static Rotation,
+        Option<&'static ComputedCenterOfMass>,
+    ),
+    Without<Puppet>,
+>;
+
+/// Velocity of a point on a moving platform.
+///
+/// Kinematic and dynamic rigid bodies report their velocity directly.
+/// Returns `None` for static or non-physics entities and for bodies without any velocity,
+/// which fall back to the difference of their [`GlobalTransform`] since the last tick.
+pub(crate) fn platform_velocity_at(
+    point: Vec3,
+    platform: Entity,
+    collider_query: &Query<&ColliderOf>,
+    body_query: &PlatformBodyQuery,
+) -> Option<Vec3> {
+    let body = collider_query
+        .get(platform)
+        .map(|collider_of| collider_of.body)
+        .unwrap_or(platform);
+
+    let (rigid_body, linear_velocity, angular_velocity, position, rotation, center_of_mass) =
+        body_query.get(body).ok()?;
+    if rigid_body.is_static()
+        || (linear_velocity.0 == Vec3::ZERO && angular_velocity.0 == Vec3::ZERO)
+    {
+        return None;
+    }
+
+    let center_of_mass =
+        position.0 + rotation.0 * center_of_mass.map_or(Vec3::ZERO, |center| center.0);
+    Some(linear_velocity.0 + angular_velocity.0.cross(point - center_of_mass))
 }
 
 fn handle_moving_platforms(
@@ -154,6 +633,9 @@ fn handle_moving_platforms(
         &mut HandleMovingPlatforms,
     )>,
     transform_query: Query<&GlobalTransform, Without<Puppet>>,
+    collider_query: Query<&ColliderOf>,
+    body_query: PlatformBodyQuery,
+    surface_velocity_query: Query<&SurfaceVelocity>,
     time: Res<Time>,
 ) {
     for (mut puppet, grounded, transform, mut handle_moving_platforms) in puppet_query.iter_mut() {
@@ -162,7 +644,31 @@ fn handle_moving_platforms(
             continue;
         };
 
-        let current_global_transform = transform_query.get(grounded.0).unwrap();
+        if let Ok(surface_velocity) = surface_velocity_query.get(grounded.0) {
+            let rotation = transform_query
+                .get(grounded.0)
+                .map_or(Quat::IDENTITY, |global_transform| {
+                    global_transform.rotation()
+                });
+            puppet.target_position += rotation * surface_velocity.0;
+        }
+
+        if let Some(platform_vel) = platform_velocity_at(
+            transform.translation,
+            grounded.0,
+            &collider_query,
+            &body_query,
+        ) {
+            handle_moving_platforms.previous_global_transform =
+                transform_query.get(grounded.0).ok().copied();
+            puppet.target_position += platform_vel;
+            continue;
+        }
+
+        let Ok(current_global_transform) = transform_query.get(grounded.0) else {
+            handle_moving_platforms.previous_global_transform = None;
+            continue;
+        };
 
         let Some(previous_global_transform) =
             &mut handle_moving_platforms.previous_global_transform
@@ -187,6 +693,93 @@ fn handle_moving_platforms(
     }
 }
 
+/// Moves grounded puppets along with the vertical motion of their platform.
+///
+/// This runs after the physics step moved the platform, so the puppet is lifted out of a rising
+/// platform or lowered onto a sinking one before the next tick checks the ground against it.
+#[allow(clippy::complexity)]
+fn carry_vertical_platforms(
+    mut puppet_query: Query<(
+        Entity,
+        &Puppet,
+        Option<&Grounded>,
+        &Collider,
+        &mut Transform,
+        &mut HandleMovingPlatforms,
+        Option<&DropThrough>,
+    )>,
+    platform_query: Query<(&Position, &Rotation), Without<Puppet>>,
+    spatial_query: SpatialQuery,
+    surface_query: Query<SurfaceData>,
+) {
+    for (
+        entity,
+        puppet,
+        grounded,
+        collider,
+        mut transform,
+        mut handle_moving_platforms,
+        drop_through,
+    ) in puppet_query.iter_mut()
+    {
+        let current = grounded.and_then(|grounded| {
+            platform_query
+                .get(grounded.0)
+                .ok()
+                .map(|(position, rotation)| (grounded.0, position.0, rotation.0))
+        });
+        let previous =
+            std::mem::replace(&mut handle_moving_platforms.previous_platform_pose, current);
+
+        let (
+            Some((platform, position, rotation)),
+            Some((previous_platform, previous_position, previous_rotation)),
+        ) = (current, previous)
+        else {
+            continue;
+        };
+        if platform != previous_platform {
+            continue;
+        }
+
+        let local_translation =
+            previous_rotation.inverse() * (transform.translation - previous_position);
+        let displacement = (position + rotation * local_translation - transform.translation).y;
+
+        // Don't pull a puppet that just jumped off the platform back down
+        if displacement == 0.0 || (displacement < 0.0 && puppet.gravity_velocity > 0.0) {
+            continue;
+        }
+
+        // A rising platform overlaps the puppet, so it has to be ignored when moving up
+        let mut query_filter = puppet_filter(entity, drop_through);
+        if displacement > 0.0 {
+            query_filter.excluded_entities.insert(platform);
+        }
+
+        let direction = if displacement > 0.0 {
+            Dir3::Y
+        } else {
+            Dir3::NEG_Y
+        };
+        let distance = if let Some(hit) = cast_puppet_shape(
+            &spatial_query,
+            collider,
+            transform.translation,
+            direction,
+            displacement.abs() + puppet.skin_thickness,
+            query_filter,
+            &surface_query,
+        ) {
+            (hit.distance - puppet.skin_thickness).max(0.0)
+        } else {
+            displacement.abs()
+        };
+
+        transform.translation += direction * distance;
+    }
+}
+
 #[allow(clippy::complexity)]
 pub fn move_puppet(
     time: Res<Time>,
@@ -196,48 +789,114 @@ pub fn move_puppet(
         Has<Grounded>,
         &Collider,
         &mut Transform,
-        &GlobalTransform,
+        Option<&DropThrough>,
+        &PushSettings,
+        &ExternalVelocity,
     )>,
-    mut forces: Query<Forces>,
     spatial_query: SpatialQuery,
-    center_of_mass_query: Query<(&ColliderMassProperties, &GlobalTransform)>,
+    surface_query: Query<SurfaceData>,
+    mut push_params: PushParams,
 ) {
-    for (entity, mut puppet, grounded, collider, mut transform, global_transform) in
-        query.iter_mut()
+    for (
+        entity,
+        mut puppet,
+        grounded,
+        collider,
+        mut transform,
+        drop_through,
+        push_settings,
+        external,
+    ) in query.iter_mut()
     {
+        if let Some(max_fall_speed) = puppet.max_fall_speed {
+            puppet.gravity_velocity = puppet.gravity_velocity.max(-max_fall_speed);
+        }
         let gravity = Vec3::new(0.0, puppet.gravity_velocity, 0.0);
+        let query_filter = puppet_filter(entity, drop_through);
+        let mut pushes = Vec::new();
+
+        let mut horizontal = (puppet.target_position + puppet.target_velocity + external.velocity)
+            * Vec3::new(1.0, 0.0, 1.0)
+            * time.delta_secs();
+
+        // Walking uphill is handled while sliding along the slope, walking downhill leaves the
+        // ground and has to check the slope below
+        if grounded
+            && puppet.slope_speed.downhill_multiplier != 1.0
+            && let Some(hit) = cast_puppet_shape(
+                &spatial_query,
+                collider,
+                transform.translation,
+                Dir3::NEG_Y,
+                puppet.skin_thickness * 2.0,
+                query_filter.clone(),
+                &surface_query,
+            )
+            && horizontal.xz().dot(hit.normal1.xz()) > 0.0
+        {
+            horizontal *= puppet.slope_speed.multiplier(
+                hit.normal1,
+                horizontal,
+                max_slope_angle(puppet.max_slope_angle, hit.entity, &surface_query),
+            );
+        }
 
         let mut effective_translation = collide_and_slide(
-            global_transform.translation(),
-            (puppet.target_position + puppet.target_velocity)
-                * Vec3::new(1.0, 0.0, 1.0)
-                * time.delta_secs(),
+            transform.translation,
+            horizontal,
             &spatial_query,
-            &SpatialQueryFilter::default().with_excluded_entities([entity]),
+            &query_filter,
             collider,
             &puppet,
             grounded,
             0,
             false,
-            &mut forces,
-            &center_of_mass_query,
+            &surface_query,
+            &mut pushes,
         );
-        effective_translation += collide_and_slide(
-            global_transform.translation() + effective_translation,
+        let gravity_translation = collide_and_slide(
+            transform.translation + effective_translation,
             gravity * time.delta_secs(),
             &spatial_query,
-            &SpatialQueryFilter::default().with_excluded_entities([entity]),
+            &query_filter,
             collider,
             &puppet,
             grounded,
             0,
             true,
-            &mut forces,
-            &center_of_mass_query,
+            &surface_query,
+            &mut pushes,
         );
+        effective_translation += gravity_translation;
+
+        // A puppet resting on a surface it can't stand on isn't grounded, so it only falls as
+        // fast as the surface lets it instead of building up fall speed
+        if !grounded
+            && gravity_translation.y > gravity.y * time.delta_secs()
+            && let Some(hit) = cast_puppet_shape(
+                &spatial_query,
+                collider,
+                transform.translation + effective_translation,
+                Dir3::NEG_Y,
+                puppet.skin_thickness * 2.0,
+                query_filter.clone(),
+                &surface_query,
+            )
+            && !is_walkable(&puppet, hit.entity, hit.normal1, &surface_query)
+        {
+            puppet.gravity_velocity = puppet
+                .gravity_velocity
+                .max(gravity_translation.y / time.delta_secs());
+        }
 
         transform.translation += effective_translation;
         puppet.target_position = Vec3::ZERO;
+
+        if grounded || push_settings.push_while_airborne {
+            for push in pushes {
+                push_params.push(&puppet, push_settings, push);
+            }
+        }
     }
 }
 
@@ -252,8 +911,8 @@ fn collide_and_slide(
     grounded: bool,
     depth: u32,
     gravity_pass: bool,
-    forces: &mut Query<Forces>,
-    center_of_mass_query: &Query<(&ColliderMassProperties, &GlobalTransform)>,
+    surface_query: &Query<SurfaceData>,
+    pushes: &mut Vec<Push>,
 ) -> Vec3 {
     if vel.length() == 0.0 {
         return Vec3::ZERO;
@@ -275,20 +934,42 @@ fn collide_and_slide(
         &ShapeCastConfig::from_max_distance(vel.length() + puppet.skin_thickness),
         query_filter,
     ) {
+        if passes_through(&hit, vel, surface_query) {
+            let mut query_filter = query_filter.clone();
+            query_filter.excluded_entities.insert(hit.entity);
+            return collide_and_slide(
+                pos,
+                vel,
+                spatial_query,
+                &query_filter,
+                collider,
+                puppet,
+                grounded,
+                depth,
+                gravity_pass,
+                surface_query,
+                pushes,
+            );
+        }
+
         let mut effective_vel = vel.normalize_or_zero() * (hit.distance - puppet.skin_thickness);
         let mut remaining_vel = vel - effective_vel;
-        let angle = Vec3::Y.angle_between(hit.normal1).to_degrees();
 
         if effective_vel.length() <= puppet.skin_thickness {
             effective_vel = Vec3::ZERO;
         }
 
         // Check for max slope
-        if angle <= puppet.max_slope_angle {
+        if is_walkable(puppet, hit.entity, hit.normal1, surface_query) {
             if gravity_pass {
                 return effective_vel;
             }
-            remaining_vel = project_and_scale(remaining_vel, hit.normal1);
+            remaining_vel = slope_velocity(
+                puppet,
+                remaining_vel,
+                hit.normal1,
+                max_slope_angle(puppet.max_slope_angle, hit.entity, surface_query),
+            );
         } else {
             // Hit wall
             // Scale slide distance by angle of collision
@@ -304,24 +985,26 @@ fn collide_and_slide(
                 let mut step_vel = vel + (-hit.normal1 * puppet.step_move_distance);
 
                 // 1. Cast collision shape up a step-height
-                if let Some(step_hit) = spatial_query.cast_shape(
+                if let Some(step_hit) = cast_puppet_shape(
+                    spatial_query,
                     collider,
                     pos,
-                    Quat::default(),
                     Dir3::Y,
-                    &ShapeCastConfig::from_max_distance(step_height + puppet.skin_thickness),
-                    query_filter,
+                    step_height + puppet.skin_thickness,
+                    query_filter.clone(),
+                    surface_query,
                 ) {
                     step_height = step_hit.distance - puppet.skin_thickness;
                 }
                 // 2. Cast collision shape along velocity direction
-                if let Some(step_hit) = spatial_query.cast_shape(
+                if let Some(step_hit) = cast_puppet_shape(
+                    spatial_query,
                     collider,
                     pos + (Vec3::Y * step_height),
-                    Quat::default(),
                     Dir3::new(step_vel.normalize_or_zero()).unwrap(),
-                    &ShapeCastConfig::from_max_distance(step_vel.length() + puppet.skin_thickness),
-                    query_filter,
+                    step_vel.length() + puppet.skin_thickness,
+                    query_filter.clone(),
+                    surface_query,
                 ) {
                     step_vel =
                         vel.normalize_or_zero() * (step_hit.distance - puppet.skin_thickness);
@@ -330,23 +1013,28 @@ fn collide_and_slide(
                     step_vel = Vec3::ZERO;
                 }
                 // 3. Cast collision shape down new vel.y - pos.y
-                if let Some(step_hit) = spatial_query.cast_shape(
+                if let Some(step_hit) = cast_puppet_shape(
+                    spatial_query,
                     collider,
                     pos + step_vel + (Vec3::Y * step_height),
-                    Quat::default(),
                     Dir3::NEG_Y,
-                    &ShapeCastConfig::from_max_distance(step_height + puppet.skin_thickness),
-                    query_filter,
+                    step_height + puppet.skin_thickness,
+                    query_filter.clone(),
+                    surface_query,
                 ) {
                     step_height -= step_hit.distance - puppet.skin_thickness;
-                    let step_angle = Vec3::Y.angle_between(step_hit.normal1).to_degrees();
-                    if step_angle <= puppet.max_slope_angle {
+                    if is_walkable(puppet, step_hit.entity, step_hit.normal1, surface_query)
+                        && is_steppable(hit.entity, surface_query)
+                        && is_steppable(step_hit.entity, surface_query)
+                    {
                         return Vec3::new(step_vel.x, 0.0, step_vel.z) + (Vec3::Y * step_height);
                     }
                 }
-                if let Ok(mut forces) = forces.get_mut(hit.entity) {
-                    forces.apply_linear_impulse_at_point(vel, hit.point1);
-                }
+                pushes.push(Push {
+                    collider: hit.entity,
+                    displacement: vel,
+                    point: hit.point1,
+                });
 
                 // Treat the collision normal as a flat wall to fix jitter when sliding along steep
                 // angles
@@ -355,7 +1043,24 @@ fn collide_and_slide(
                     Vec3::new(hit.normal1.x, 0.0, hit.normal1.z),
                 ) * scale;
             } else {
+                pushes.push(Push {
+                    collider: hit.entity,
+                    displacement: vel,
+                    point: hit.point1,
+                });
                 remaining_vel = project_and_scale(remaining_vel, hit.normal1) * scale;
+
+                // Slide off flat surfaces the puppet isn't allowed to stand on, at most the skin
+                // thickness per tick so the slide doesn't speed up with the fall speed.
+                // Flat tops have no horizontal normal, so slide away from the contact point instead
+                if gravity_pass && remaining_vel.xz().length() <= puppet.skin_thickness {
+                    let mut away = hit.normal1.with_y(0.0);
+                    if away.length() < 0.01 {
+                        away = (pos - hit.point1).with_y(0.0);
+                    }
+                    remaining_vel = away.try_normalize().unwrap_or(Vec3::X)
+                        * (vel - effective_vel).length().min(puppet.skin_thickness);
+                }
             }
         }
 
@@ -370,8 +1075,8 @@ fn collide_and_slide(
                 grounded,
                 depth + 1,
                 gravity_pass,
-                forces,
-                center_of_mass_query,
+                surface_query,
+                pushes,
             )
     } else {
         vel
@@ -392,6 +1097,21 @@ fn project_onto_plane(rhs: Vec3, plane: Vec3) -> Vec3 {
     }
 }
 
+/// Projects the velocity onto walkable ground, applying the [`SlopeSpeed`] of the puppet
+fn slope_velocity(puppet: &Puppet, velocity: Vec3, normal: Vec3, max_slope_angle: f32) -> Vec3 {
+    let mut projected = project_and_scale(velocity, normal);
+    if puppet.slope_speed.preserve_horizontal_speed {
+        let horizontal_speed = projected.xz().length();
+        if horizontal_speed > 0.0 {
+            projected *= velocity.xz().length() / horizontal_speed;
+        }
+    }
+    projected
+        * puppet
+            .slope_speed
+            .multiplier(normal, velocity, max_slope_angle)
+}
+
 fn project_and_scale(rhs: Vec3, plane: Vec3) -> Vec3 {
     project_onto_plane(rhs, plane).normalize_or_zero() * rhs.length()
 }
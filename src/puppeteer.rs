@@ -4,27 +4,58 @@ use avian3d::prelude::GravityScale;
 use bevy::prelude::*;
 
 use crate::{
-    puppet::{Grounded, Puppet},
+    bounce::Bouncing,
+    external::Launched,
+    modifiers::{ModifierStat, MovementModifiers},
+    puppet::{DropThrough, Grounded, OneWayPlatform, Puppet, PuppetSurface},
     puppet_rig::LastPosition,
 };
 
 #[derive(Component, Reflect)]
-#[require(Puppet, PuppeteerInput, LastPosition)]
+#[require(
+    Puppet,
+    PuppeteerInput,
+    LastPosition,
+    JumpParameters,
+    MovementModifiers
+)]
 pub struct Puppeteer {
+    /// Acceleration in units/s²
     pub acceleration: f32,
+    /// Deceleration in units/s² when there is no input
     pub deceleration: f32,
+    /// Multiplies [`Self::acceleration`] depending on the current speed, see [`SpeedCurve`]
+    pub acceleration_curve: Option<SpeedCurve>,
+    /// Multiplies [`Self::deceleration`] depending on the current speed, see [`SpeedCurve`]
+    pub deceleration_curve: Option<SpeedCurve>,
+    /// Acceleration in the air in units/s²
     pub air_acceleration: f32,
+    /// Deceleration in the air in units/s²
     pub air_deceleration: f32,
+    /// Acceleration in the air in units/s² when moving against the current direction
     pub air_turn_speed: f32,
     pub max_speed: f32,
+    /// Acceleration in units/s² when moving against the current direction
     pub turn_speed: f32,
+    /// How input changes the horizontal velocity
+    pub movement_model: MovementModel,
     pub gravity: f32,
+    /// Terminal velocity, the fastest the puppeteer can fall
+    pub max_fall_speed: f32,
+    /// Fastest fall speed while [`Gliding`]
+    pub glide_fall_speed: f32,
+    /// Fastest fall speed while [`WallSliding`]
+    pub wall_slide_fall_speed: f32,
 
     pub jump_height: f32,
-    pub time_to_jump_apex: f32,
+    /// How long the jump takes, see [`JumpTiming`]
+    pub jump_timing: JumpTiming,
+    /// Gravity multiplier while falling, ignored by [`JumpTiming::RiseFall`]
     pub downward_movement_multiplier: f32,
-    pub max_air_jumps: u32,
-    pub jump_cutoff: f32,
+    /// Height reached when the jump is released right away
+    pub min_jump_height: f32,
+    /// One profile per jump in the air, the length is the number of air jumps
+    pub air_jumps: Vec<JumpProfile>,
 
     pub coyote_time: Duration,
     pub jump_buffer: Duration,
@@ -33,19 +64,25 @@ pub struct Puppeteer {
 impl Default for Puppeteer {
     fn default() -> Self {
         Self {
-            acceleration: 0.7,
-            deceleration: 0.7,
-            air_acceleration: 0.6,
-            air_deceleration: 0.1,
-            air_turn_speed: 0.1,
+            acceleration: 44.8,
+            deceleration: 44.8,
+            acceleration_curve: None,
+            deceleration_curve: None,
+            air_acceleration: 38.4,
+            air_deceleration: 6.4,
+            air_turn_speed: 6.4,
             max_speed: 7.0,
-            turn_speed: 2.0,
+            turn_speed: 128.0,
+            movement_model: MovementModel::default(),
             gravity: 9.81,
+            max_fall_speed: 50.0,
+            glide_fall_speed: 2.0,
+            wall_slide_fall_speed: 3.0,
             jump_height: 1.0,
-            time_to_jump_apex: 0.3,
+            jump_timing: JumpTiming::default(),
             downward_movement_multiplier: 1.0,
-            max_air_jumps: 0,
-            jump_cutoff: 1.5,
+            min_jump_height: 0.5,
+            air_jumps: Vec::new(),
 
             coyote_time: Duration::from_millis(150),
             jump_buffer: Duration::from_millis(150),
@@ -53,13 +90,447 @@ impl Default for Puppeteer {
     }
 }
 
+/// Maps the current speed, relative to [`Puppeteer::max_speed`], to a multiplier
+/// of the acceleration or deceleration.
+///
+/// An acceleration curve from `2.0` to `0.5` gives a snappy start that eases into the top speed.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct SpeedCurve {
+    /// Multiplier when standing still
+    pub start: f32,
+    /// Multiplier at [`Puppeteer::max_speed`]
+    pub end: f32,
+    pub ease: EaseFunction,
+}
+
+impl Default for SpeedCurve {
+    fn default() -> Self {
+        Self {
+            start: 1.0,
+            end: 1.0,
+            ease: EaseFunction::Linear,
+        }
+    }
+}
+
+impl SpeedCurve {
+    /// The multiplier at `speed_fraction` of the max speed
+    pub fn sample(&self, speed_fraction: f32) -> f32 {
+        EasingCurve::new(self.start, self.end, self.ease).sample_clamped(speed_fraction)
+    }
+}
+
+/// How a [`Puppeteer`] accelerates horizontally.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq)]
+pub enum MovementModel {
+    /// Moves towards the input velocity with the acceleration and deceleration of the [`Puppeteer`]
+    #[default]
+    Smooth,
+    /// Quake style movement with ground friction and air strafing.
+    /// Speed is only capped while accelerating, so momentum is kept in the air.
+    Quake(QuakeMovement),
+}
+
+/// Settings of [`MovementModel::Quake`], speeds are relative to [`Puppeteer::max_speed`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct QuakeMovement {
+    /// Acceleration on the ground
+    pub ground_acceleration: f32,
+    /// Acceleration in the air
+    pub air_acceleration: f32,
+    /// Speed up to which the input can accelerate in the air, as a fraction of the max speed
+    pub air_speed_cap: f32,
+    /// How fast the puppeteer slows down on the ground
+    pub friction: f32,
+    /// Below this fraction of the max speed friction slows down as if moving this fast,
+    /// so the puppeteer comes to a stop
+    pub stop_speed: f32,
+}
+
+impl Default for QuakeMovement {
+    fn default() -> Self {
+        Self {
+            ground_acceleration: 10.0,
+            air_acceleration: 10.0,
+            air_speed_cap: 0.15,
+            friction: 6.0,
+            stop_speed: 0.2,
+        }
+    }
+}
+
+impl QuakeMovement {
+    /// Adds speed along `wish_direction` until the velocity reaches `max_speed` in that direction
+    fn accelerate(
+        velocity: Vec3,
+        wish_direction: Vec3,
+        wish_speed: f32,
+        max_speed: f32,
+        acceleration: f32,
+        delta: f32,
+    ) -> Vec3 {
+        let add_speed = max_speed - velocity.dot(wish_direction);
+        if add_speed <= 0.0 {
+            return velocity;
+        }
+        velocity + wish_direction * (acceleration * wish_speed * delta).min(add_speed)
+    }
+
+    fn friction(&self, velocity: Vec3, max_speed: f32, friction: f32, delta: f32) -> Vec3 {
+        let speed = velocity.length();
+        if speed < 0.0001 {
+            return Vec3::ZERO;
+        }
+        let drop = speed.max(self.stop_speed * max_speed) * friction * delta;
+        velocity * ((speed - drop).max(0.0) / speed)
+    }
+}
+
+/// Defines how long a jump of [`Puppeteer::jump_height`] takes.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub enum JumpTiming {
+    /// Seconds until the highest point of the jump is reached
+    Apex(f32),
+    /// Horizontal distance covered at [`Puppeteer::max_speed`] until landing at the starting height
+    Distance(f32),
+    /// Seconds to rise to the highest point and to fall back down to the starting height
+    RiseFall { rise: f32, fall: f32 },
+}
+
+impl Default for JumpTiming {
+    fn default() -> Self {
+        Self::Apex(0.3)
+    }
+}
+
+/// Configuration of a single air jump, see [`Puppeteer::air_jumps`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct JumpProfile {
+    /// Height of the jump
+    pub height: f32,
+    /// Horizontal speed added in the direction the puppeteer is moving
+    pub horizontal_boost: f32,
+    pub velocity_reset: VelocityReset,
+    /// Gravity multiplier from this jump until landing or jumping again
+    pub gravity_multiplier: f32,
+}
+
+impl Default for JumpProfile {
+    fn default() -> Self {
+        Self {
+            height: 1.0,
+            horizontal_boost: 0.0,
+            velocity_reset: VelocityReset::default(),
+            gravity_multiplier: 1.0,
+        }
+    }
+}
+
+/// What happens to the velocity of the puppet when a jump starts.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VelocityReset {
+    /// The jump starts from zero vertical velocity, unless the puppet already rises faster
+    #[default]
+    Vertical,
+    /// The jump is added to the current vertical velocity
+    None,
+    /// The jump starts from zero vertical and horizontal velocity
+    All,
+}
+
+/// Jump values derived from the [`Puppeteer`] settings, updated whenever it changes.
+#[derive(Component, Default, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+pub struct JumpParameters {
+    /// Seconds until the highest point of the jump is reached
+    pub time_to_apex: f32,
+    /// Seconds from the highest point back down to the starting height
+    pub fall_time: f32,
+    /// Horizontal distance of a full jump at [`Puppeteer::max_speed`]
+    pub distance: f32,
+    /// Initial vertical velocity of the jump
+    pub jump_speed: f32,
+    /// [`GravityScale`] while rising
+    pub gravity_scale: f32,
+    /// Gravity multiplier while falling
+    pub fall_multiplier: f32,
+    /// Gravity multiplier after releasing the jump while rising
+    pub cutoff_multiplier: f32,
+}
+
+impl Puppeteer {
+    /// Seconds until the highest point of the jump is reached
+    pub fn time_to_jump_apex(&self) -> f32 {
+        match self.jump_timing {
+            JumpTiming::Apex(time) => time,
+            JumpTiming::Distance(distance) => {
+                let fall_ratio = 1.0 / self.downward_movement_multiplier.max(f32::EPSILON).sqrt();
+                distance / (self.max_speed * (1.0 + fall_ratio)).max(f32::EPSILON)
+            }
+            JumpTiming::RiseFall { rise, .. } => rise,
+        }
+    }
+
+    /// Gravity multiplier while falling
+    pub fn fall_multiplier(&self) -> f32 {
+        match self.jump_timing {
+            JumpTiming::RiseFall { rise, fall } => (rise / fall.max(f32::EPSILON)).powi(2),
+            _ => self.downward_movement_multiplier,
+        }
+    }
+
+    /// Gravity multiplier that stops a released jump of `jump_height` at [`Self::min_jump_height`]
+    pub fn jump_cutoff(&self, jump_height: f32) -> f32 {
+        (jump_height / self.min_jump_height.max(f32::EPSILON)).max(1.0)
+    }
+
+    /// The [`GravityScale`] that reaches [`Self::jump_height`] in [`Self::time_to_jump_apex`],
+    /// multiplied by the given [`GravityMultiplier`]
+    pub fn gravity_scale(&self, gravity_multiplier: f32) -> f32 {
+        let time_to_jump_apex = self.time_to_jump_apex().max(f32::EPSILON);
+        let new_gravity = (-2.0 * self.jump_height) / (time_to_jump_apex * time_to_jump_apex);
+
+        (new_gravity / -self.gravity) * gravity_multiplier
+    }
+
+    /// The [`GravityMultiplier`] for the given vertical velocity.
+    /// Releasing the jump while moving up cuts the jump of `jump_height` off.
+    pub fn gravity_multiplier(
+        &self,
+        gravity_velocity: f32,
+        is_jumping: bool,
+        jump_height: f32,
+    ) -> f32 {
+        if gravity_velocity > 0.01 {
+            if is_jumping {
+                1.0
+            } else {
+                self.jump_cutoff(jump_height)
+            }
+        } else if gravity_velocity < -0.01 {
+            self.fall_multiplier()
+        } else {
+            1.0
+        }
+    }
+
+    /// The initial vertical velocity of a jump with the given [`GravityScale`]
+    pub fn jump_speed(&self, gravity_scale: f32) -> f32 {
+        self.jump_speed_to(self.jump_height, gravity_scale)
+    }
+
+    /// The initial vertical velocity to reach `height` with the given [`GravityScale`]
+    pub fn jump_speed_to(&self, height: f32, gravity_scale: f32) -> f32 {
+        (-2.0 * -self.gravity * gravity_scale * height).sqrt()
+    }
+
+    /// The profile of a jump, index `0` is the jump from the ground and `n` the nth air jump
+    pub fn jump_profile(&self, index: u32) -> Option<JumpProfile> {
+        match index {
+            0 => Some(JumpProfile {
+                height: self.jump_height,
+                ..default()
+            }),
+            _ => self.air_jumps.get(index as usize - 1).copied(),
+        }
+    }
+
+    /// The index of the jump that starts when jumping now, see [`Self::jump_profile`].
+    /// `None` if the puppeteer can't jump
+    pub fn next_jump_index(
+        &self,
+        is_grounded: bool,
+        coyote_time: Option<&CoyoteTime>,
+        air_jump_count: Option<&AirJumpCount>,
+    ) -> Option<u32> {
+        if is_grounded || coyote_time.is_some_and(|t| !t.is_finished()) {
+            return Some(0);
+        }
+        let index = air_jump_count.map_or(0, |jumps| jumps.0) + 1;
+        (index as usize <= self.air_jumps.len()).then_some(index)
+    }
+
+    /// The vertical and horizontal velocity of a puppet right after a jump with the given profile
+    /// starts from `gravity_velocity` and `target_velocity`
+    pub fn jump_velocity(
+        &self,
+        profile: JumpProfile,
+        gravity_velocity: f32,
+        target_velocity: Vec3,
+        modifiers: &MovementModifiers,
+    ) -> (f32, Vec3) {
+        let mut jump_speed = self.jump_speed_to(
+            modifiers.apply(ModifierStat::JumpHeight, profile.height),
+            self.gravity_scale(profile.gravity_multiplier),
+        );
+
+        match profile.velocity_reset {
+            VelocityReset::Vertical => {
+                if gravity_velocity > 0.0 {
+                    jump_speed = (jump_speed - gravity_velocity).max(0.0);
+                } else if gravity_velocity < 0.0 {
+                    jump_speed += gravity_velocity.abs();
+                }
+            }
+            VelocityReset::None => {}
+            VelocityReset::All => {
+                jump_speed -= gravity_velocity;
+            }
+        }
+
+        let direction = (target_velocity * Vec3::new(1.0, 0.0, 1.0)).normalize_or_zero();
+        let mut target_velocity = target_velocity;
+        if profile.velocity_reset == VelocityReset::All {
+            target_velocity = Vec3::ZERO;
+        }
+        target_velocity += direction * profile.horizontal_boost;
+
+        (gravity_velocity + jump_speed, target_velocity)
+    }
+
+    /// The horizontal velocity after one tick of input.
+    /// `surface` is the surface the puppeteer stands on, `None` while airborne
+    #[allow(clippy::too_many_arguments)]
+    pub fn horizontal_velocity(
+        &self,
+        velocity: Vec3,
+        move_direction: Vec3,
+        speed_multiplier: f32,
+        surface: Option<PuppetSurface>,
+        target_position: Vec3,
+        modifiers: &MovementModifiers,
+        delta: f32,
+    ) -> Vec3 {
+        let is_grounded = surface.is_some();
+        let surface = surface.unwrap_or_default();
+        let wish_direction = move_direction.normalize_or_zero();
+        let max_speed = modifiers.apply(ModifierStat::MaxSpeed, self.max_speed);
+        let wish_speed = max_speed * surface.max_speed_multiplier * speed_multiplier;
+        // Modifiers change the base rates, surfaces and curves scale the modified rates
+        let accelerate =
+            |acceleration: f32| modifiers.apply(ModifierStat::Acceleration, acceleration);
+
+        match self.movement_model {
+            MovementModel::Smooth => {
+                let speed_fraction = velocity.xz().length() / max_speed.max(f32::EPSILON);
+                let acceleration_multiplier = self
+                    .acceleration_curve
+                    .map_or(1.0, |curve| curve.sample(speed_fraction));
+                let deceleration_multiplier = self
+                    .deceleration_curve
+                    .map_or(1.0, |curve| curve.sample(speed_fraction));
+
+                let acceleration = acceleration_multiplier
+                    * if is_grounded {
+                        accelerate(self.acceleration) * surface.acceleration_multiplier
+                    } else {
+                        accelerate(self.air_acceleration)
+                    };
+                let deceleration = deceleration_multiplier
+                    * if is_grounded {
+                        accelerate(self.deceleration) * surface.deceleration_multiplier
+                    } else {
+                        accelerate(self.air_deceleration)
+                    };
+                let turn_speed = if is_grounded {
+                    accelerate(self.turn_speed) * surface.acceleration_multiplier
+                } else {
+                    accelerate(self.air_turn_speed)
+                };
+
+                let max_speed_change = if move_direction.length() > 0.1 {
+                    if target_position.length() < 0.1 {
+                        acceleration
+                    } else {
+                        acceleration.lerp(
+                            turn_speed,
+                            (-wish_direction.dot(target_position.normalize()) + 1.0) * 0.5,
+                        )
+                    }
+                } else {
+                    deceleration
+                };
+
+                move_towards(
+                    velocity,
+                    wish_direction * wish_speed,
+                    max_speed_change * delta,
+                )
+            }
+            MovementModel::Quake(quake) => {
+                if is_grounded {
+                    let velocity = quake.friction(
+                        velocity,
+                        max_speed,
+                        quake.friction * surface.deceleration_multiplier,
+                        delta,
+                    );
+                    QuakeMovement::accelerate(
+                        velocity,
+                        wish_direction,
+                        wish_speed,
+                        wish_speed,
+                        accelerate(quake.ground_acceleration) * surface.acceleration_multiplier,
+                        delta,
+                    )
+                } else {
+                    QuakeMovement::accelerate(
+                        velocity,
+                        wish_direction,
+                        wish_speed,
+                        wish_speed.min(quake.air_speed_cap * max_speed),
+                        accelerate(quake.air_acceleration),
+                        delta,
+                    )
+                }
+            }
+        }
+    }
+
+    /// The fastest the puppeteer can fall in its current state
+    pub fn fall_speed_limit(&self, gliding: bool, wall_sliding: bool) -> f32 {
+        let mut limit = self.max_fall_speed;
+        if gliding {
+            limit = limit.min(self.glide_fall_speed);
+        }
+        if wall_sliding {
+            limit = limit.min(self.wall_slide_fall_speed);
+        }
+        limit
+    }
+
+    /// All derived jump values
+    pub fn jump_parameters(&self) -> JumpParameters {
+        let time_to_apex = self.time_to_jump_apex();
+        let fall_multiplier = self.fall_multiplier();
+        let fall_time = time_to_apex / fall_multiplier.max(f32::EPSILON).sqrt();
+        let gravity_scale = self.gravity_scale(1.0);
+
+        JumpParameters {
+            time_to_apex,
+            fall_time,
+            distance: self.max_speed * (time_to_apex + fall_time),
+            jump_speed: self.jump_speed(gravity_scale),
+            gravity_scale,
+            fall_multiplier,
+            cutoff_multiplier: self.jump_cutoff(self.jump_height),
+        }
+    }
+}
+
 #[derive(Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct PuppeteerInput {
     pub move_direction: Vec3,
     pub speed_multiplier: f32,
+    /// Speed multiplier of a [`PuppeteerPath`](crate::path::PuppeteerPath) for the current tick,
+    /// used instead of [`Self::speed_multiplier`]
+    pub(crate) path_multiplier: Option<f32>,
+    /// Speed scale picked by [`Avoidance`](crate::avoidance::Avoidance) for the current tick
+    pub(crate) avoidance_multiplier: Option<f32>,
     jump_start: bool,
     jump_canceled: bool,
+    drop_through: bool,
 }
 
 impl PuppeteerInput {
@@ -77,6 +548,11 @@ impl PuppeteerInput {
     pub fn stop_jump(&mut self) {
         self.jump_canceled = true;
     }
+
+    /// Drop through the [`OneWayPlatform`] the puppeteer is standing on (usually down + jump)
+    pub fn drop_through(&mut self) {
+        self.drop_through = true;
+    }
 }
 
 /// Component indicating that the entity is jumping with a timer defining the duration of the jump
@@ -85,6 +561,18 @@ impl PuppeteerInput {
 #[component(storage = "SparseSet")]
 pub struct Jumping;
 
+/// Insert to limit falling to [`Puppeteer::glide_fall_speed`]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[component(storage = "SparseSet")]
+pub struct Gliding;
+
+/// Insert to limit falling to [`Puppeteer::wall_slide_fall_speed`]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[component(storage = "SparseSet")]
+pub struct WallSliding;
+
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 pub struct DominantCollider;
@@ -93,6 +581,14 @@ pub struct DominantCollider;
 #[component(storage = "SparseSet")]
 pub struct AirJumpCount(pub u32);
 
+/// Message sent when a puppeteer jumps.
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct Jumped {
+    pub entity: Entity,
+    /// `0` for a jump from the ground, `n` for the nth air jump
+    pub index: u32,
+}
+
 #[derive(Component, Deref, DerefMut, Reflect)]
 #[component(storage = "SparseSet")]
 pub struct CoyoteTime(pub Timer);
@@ -104,79 +600,236 @@ pub struct JumpBuffer(pub Timer);
 #[derive(Component, Default, Deref, Reflect)]
 pub struct GravityMultiplier(pub f32);
 
+/// Tracks the current fall of an airborne puppeteer
+#[derive(Component, Reflect)]
+#[component(storage = "SparseSet")]
+pub struct Falling {
+    /// Highest point reached since leaving the ground
+    pub start_height: f32,
+    /// Fastest downward speed reached since leaving the ground
+    pub peak_speed: f32,
+}
+
+/// Message sent when a puppeteer lands.
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct FallImpact {
+    pub entity: Entity,
+    /// The entity the puppeteer landed on
+    pub surface: Entity,
+    /// Height difference between the highest point of the fall and the landing
+    pub distance: f32,
+    /// Fastest downward speed reached during the fall
+    pub speed: f32,
+    /// Damage calculated by the [`FallDamage`] of the puppeteer, `0.0` without one
+    pub damage: f32,
+}
+
+/// Add this component to a [`Puppeteer`] to calculate the [`FallImpact::damage`] of a landing.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct FallDamage {
+    /// Landing slower than this doesn't cause damage
+    pub min_speed: f32,
+    /// Landing with this speed or faster causes [`Self::max_damage`]
+    pub max_speed: f32,
+    pub max_damage: f32,
+    /// How the damage grows between [`Self::min_speed`] and [`Self::max_speed`]
+    pub curve: EaseFunction,
+}
+
+impl Default for FallDamage {
+    fn default() -> Self {
+        Self {
+            min_speed: 12.0,
+            max_speed: 30.0,
+            max_damage: 100.0,
+            curve: EaseFunction::QuadraticIn,
+        }
+    }
+}
+
+impl FallDamage {
+    /// Damage of a landing with the given speed
+    pub fn damage(&self, speed: f32) -> f32 {
+        if speed < self.min_speed {
+            return 0.0;
+        }
+        let t = (speed - self.min_speed) / (self.max_speed - self.min_speed).max(f32::EPSILON);
+        self.curve.sample_clamped(t) * self.max_damage
+    }
+}
+
+/// Multiplies the [`FallImpact::damage`] of puppeteers landing on this collider.
+/// Use `0.0` for water, trampolines and other surfaces that shouldn't hurt.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Deref)]
+#[reflect(Component)]
+pub struct FallDamageMultiplier(pub f32);
+
+#[allow(clippy::complexity)]
 pub fn movement(
     mut query: Query<(
         &Puppeteer,
         &mut PuppeteerInput,
         &mut Puppet,
-        Has<Grounded>,
+        Option<&Grounded>,
+        Option<Ref<Falling>>,
         &GravityScale,
+        Has<Gliding>,
+        Has<WallSliding>,
+        &MovementModifiers,
     )>,
+    surface_query: Query<&PuppetSurface>,
     time: Res<Time>,
 ) {
-    for (controller, mut move_action, mut puppet, is_grounded, gravity_scale) in &mut query {
-        let acceleration = if is_grounded {
-            controller.acceleration
-        } else {
-            controller.air_acceleration
-        };
-        let deceleration = if is_grounded {
-            controller.deceleration
-        } else {
-            controller.air_deceleration
-        };
-        let turn_speed = if is_grounded {
-            controller.turn_speed
-        } else {
-            controller.air_turn_speed
-        };
-
-        let desired_velocity = move_action.move_direction.normalize_or_zero()
-            * controller.max_speed
-            * move_action.speed_multiplier;
-
-        let max_speed_change = if move_action.move_direction.length() > 0.1 {
-            if puppet.target_position.length() < 0.1 {
-                acceleration
-            } else {
-                acceleration.lerp(
-                    turn_speed,
-                    (-move_action
-                        .move_direction
-                        .normalize()
-                        .dot(puppet.target_position.normalize())
-                        + 1.0)
-                        * 0.5,
-                )
-            }
-        } else {
-            deceleration
+    for (
+        controller,
+        mut move_action,
+        mut puppet,
+        grounded,
+        falling,
+        gravity_scale,
+        gliding,
+        wall_sliding,
+        modifiers,
+    ) in &mut query
+    {
+        let is_grounded = grounded.is_some();
+        let surface =
+            grounded.map(|grounded| surface_query.get(grounded.0).copied().unwrap_or_default());
+        // A jump fired this tick leaves the ground before friction is applied, like Quake's
+        // PM_CheckJump, so bunny hopping doesn't lose speed on the landing tick
+        let surface = match controller.movement_model {
+            MovementModel::Quake(_) if move_action.jump_start => None,
+            _ => surface,
         };
 
-        puppet.target_velocity =
-            move_towards(puppet.target_velocity, desired_velocity, max_speed_change);
+        puppet.target_velocity = controller.horizontal_velocity(
+            puppet.target_velocity,
+            move_action.move_direction,
+            move_action
+                .path_multiplier
+                .take()
+                .unwrap_or(move_action.speed_multiplier)
+                * move_action.avoidance_multiplier.take().unwrap_or(1.0),
+            surface,
+            puppet.target_position,
+            modifiers,
+            time.delta_secs(),
+        );
 
         // apply gravity
         if !is_grounded {
+            // The gravity velocity is kept while grounded to push the puppet onto the ground,
+            // walking off a ledge starts the fall from rest instead of the last landing speed
+            if falling.is_some_and(|falling| falling.is_added()) && puppet.gravity_velocity < 0.0 {
+                puppet.gravity_velocity = 0.0;
+            }
             puppet.gravity_velocity -= controller.gravity * **gravity_scale * time.delta_secs();
+            puppet.gravity_velocity = puppet
+                .gravity_velocity
+                .max(-controller.fall_speed_limit(gliding, wall_sliding));
         }
 
         move_action.move_direction = Vec3::ZERO;
     }
 }
-fn move_towards(current: Vec3, target: Vec3, max_distance_delta: f32) -> Vec3 {
+pub(crate) fn move_towards(current: Vec3, target: Vec3, max_distance_delta: f32) -> Vec3 {
     if (target - current).xz().length() <= max_distance_delta {
         return target;
     }
     current + (target - current).normalize_or_zero() * max_distance_delta
 }
 
-pub fn scale_gravity(mut query: Query<(&Puppeteer, &GravityMultiplier, &mut GravityScale)>) {
-    for (puppeteer, gravity_multiplier, mut gravity_scale) in &mut query {
-        let new_gravity = (-2.0 * puppeteer.jump_height)
-            / (puppeteer.time_to_jump_apex * puppeteer.time_to_jump_apex);
+pub fn update_jump_parameters(
+    mut query: Query<(&Puppeteer, &mut JumpParameters), Changed<Puppeteer>>,
+) {
+    for (puppeteer, mut parameters) in &mut query {
+        parameters.set_if_neq(puppeteer.jump_parameters());
+    }
+}
+
+#[allow(clippy::complexity)]
+pub fn track_falls(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &Puppet,
+            &Transform,
+            Option<&Grounded>,
+            Option<&mut Falling>,
+            Option<&FallDamage>,
+        ),
+        With<Puppeteer>,
+    >,
+    multiplier_query: Query<&FallDamageMultiplier>,
+    mut impacts: MessageWriter<FallImpact>,
+) {
+    for (entity, puppet, transform, grounded, falling, fall_damage) in &mut query {
+        let height = transform.translation.y;
+        let speed = (-puppet.gravity_velocity).max(0.0);
+
+        match (grounded, falling) {
+            (None, Some(mut falling)) => {
+                falling.start_height = falling.start_height.max(height);
+                falling.peak_speed = falling.peak_speed.max(speed);
+            }
+            (None, None) => {
+                commands.entity(entity).insert(Falling {
+                    start_height: height,
+                    peak_speed: 0.0,
+                });
+            }
+            (Some(grounded), Some(falling)) => {
+                let speed = falling.peak_speed.max(speed);
+                let multiplier = multiplier_query.get(grounded.0).map_or(1.0, |m| **m);
+                impacts.write(FallImpact {
+                    entity,
+                    surface: grounded.0,
+                    distance: (falling.start_height - height).max(0.0),
+                    speed,
+                    damage: fall_damage.map_or(0.0, |fall_damage| fall_damage.damage(speed))
+                        * multiplier,
+                });
+                commands.entity(entity).remove::<Falling>();
+            }
+            (Some(_), None) => {}
+        }
+    }
+}
+
+pub fn scale_gravity(
+    mut query: Query<(
+        &Puppeteer,
+        &GravityMultiplier,
+        &MovementModifiers,
+        &mut GravityScale,
+    )>,
+) {
+    for (puppeteer, gravity_multiplier, modifiers, mut gravity_scale) in &mut query {
+        **gravity_scale = modifiers.apply(
+            ModifierStat::Gravity,
+            puppeteer.gravity_scale(**gravity_multiplier),
+        );
+    }
+}
 
-        **gravity_scale = (new_gravity / -puppeteer.gravity) * **gravity_multiplier;
+pub fn drop_through(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PuppeteerInput, Option<&Grounded>)>,
+    one_way_query: Query<(), With<OneWayPlatform>>,
+) {
+    for (entity, mut input, grounded) in &mut query {
+        if !input.drop_through {
+            continue;
+        }
+        input.drop_through = false;
+
+        if let Some(grounded) = grounded
+            && one_way_query.contains(grounded.0)
+        {
+            commands.entity(entity).insert(DropThrough::new(grounded.0));
+        }
     }
 }
 
@@ -190,12 +843,15 @@ pub fn jumping(
         &mut Puppet,
         Has<Grounded>,
         Has<Jumping>,
+        Has<Bouncing>,
+        Has<Launched>,
         &mut GravityMultiplier,
-        &GravityScale,
         Option<&mut AirJumpCount>,
         Option<&mut CoyoteTime>,
         Has<JumpBuffer>,
+        &MovementModifiers,
     )>,
+    mut jumped: MessageWriter<Jumped>,
 ) {
     for (
         entity,
@@ -203,43 +859,35 @@ pub fn jumping(
         mut input,
         mut puppet_input,
         is_grounded,
-        is_jumping,
+        mut is_jumping,
+        is_bouncing,
+        is_launched,
         mut gravity_multiplier,
-        gravity_scale,
         air_jump_count,
         coyote_time,
         has_jump_buffer,
+        modifiers,
     ) in &mut query
     {
+        let mut jump_index = match &air_jump_count {
+            Some(jumps) if !is_grounded => jumps.0,
+            _ => 0,
+        };
+
         if input.jump_canceled {
             commands.entity(entity).remove::<Jumping>();
             input.jump_canceled = false;
+            is_jumping = false;
         }
         if input.jump_start {
             commands.entity(entity).insert(Jumping);
+            is_jumping = true;
 
-            if is_grounded || coyote_time.is_some_and(|t| !t.is_finished()) {
-                commands.entity(entity).insert(JumpBuffer(Timer::new(
-                    puppeteer.jump_buffer,
-                    TimerMode::Once,
-                )));
-                commands.entity(entity).remove::<AirJumpCount>();
-            } else if puppeteer.max_air_jumps > 0 {
-                if let Some(mut jumps) = air_jump_count {
-                    if **jumps >= puppeteer.max_air_jumps {
-                        if !has_jump_buffer {
-                            commands.entity(entity).insert(JumpBuffer(Timer::new(
-                                puppeteer.jump_buffer,
-                                TimerMode::Once,
-                            )));
-                        }
-                        continue;
-                    }
-                    **jumps += 1;
-                } else {
-                    commands.entity(entity).insert(AirJumpCount(1));
-                }
-            } else {
+            let Some(index) = puppeteer.next_jump_index(
+                is_grounded,
+                coyote_time.as_deref(),
+                air_jump_count.as_deref(),
+            ) else {
                 if !has_jump_buffer {
                     commands.entity(entity).insert(JumpBuffer(Timer::new(
                         puppeteer.jump_buffer,
@@ -247,36 +895,45 @@ pub fn jumping(
                     )));
                 }
                 continue;
+            };
+            if index == 0 {
+                commands.entity(entity).insert(JumpBuffer(Timer::new(
+                    puppeteer.jump_buffer,
+                    TimerMode::Once,
+                )));
+                commands.entity(entity).remove::<AirJumpCount>();
+            } else if let Some(mut jumps) = air_jump_count {
+                **jumps = index;
+            } else {
+                commands.entity(entity).insert(AirJumpCount(index));
             }
+            jump_index = index;
             input.jump_start = false;
 
             let mut timer = Timer::new(puppeteer.jump_buffer, TimerMode::Once);
             timer.tick(puppeteer.coyote_time);
             commands.entity(entity).insert(CoyoteTime(timer));
 
-            let mut jump_speed =
-                (-2.0 * -puppeteer.gravity * **gravity_scale * puppeteer.jump_height).sqrt();
+            (puppet_input.gravity_velocity, puppet_input.target_velocity) = puppeteer
+                .jump_velocity(
+                    puppeteer.jump_profile(jump_index).unwrap_or_default(),
+                    puppet_input.gravity_velocity,
+                    puppet_input.target_velocity,
+                    modifiers,
+                );
 
-            if puppet_input.gravity_velocity > 0.0 {
-                jump_speed = (jump_speed - puppet_input.gravity_velocity).max(0.0);
-            } else if puppet_input.gravity_velocity < 0.0 {
-                jump_speed += puppet_input.gravity_velocity.abs();
-            }
-
-            puppet_input.gravity_velocity += jump_speed;
+            jumped.write(Jumped {
+                entity,
+                index: jump_index,
+            });
         }
 
-        if puppet_input.target_position.y > 0.01 {
-            if is_jumping {
-                gravity_multiplier.0 = 1.0;
-            } else {
-                gravity_multiplier.0 = puppeteer.jump_cutoff;
-            }
-        } else if puppet_input.gravity_velocity < -0.01 {
-            gravity_multiplier.0 = puppeteer.downward_movement_multiplier;
-        } else {
-            gravity_multiplier.0 = 1.0;
-        }
+        let profile = puppeteer.jump_profile(jump_index).unwrap_or_default();
+        gravity_multiplier.0 = puppeteer.gravity_multiplier(
+            puppet_input.gravity_velocity,
+            is_jumping || is_bouncing || is_launched,
+            modifiers.apply(ModifierStat::JumpHeight, profile.height),
+        ) * profile.gravity_multiplier;
     }
 }
 
@@ -303,7 +960,9 @@ pub fn update_coyote_time(
                 )));
             }
         } else if is_grounded {
-            commands.entity(entity).remove::<CoyoteTime>();
+            commands
+                .entity(entity)
+                .remove::<(CoyoteTime, AirJumpCount)>();
         }
     }
 }
@@ -323,3 +982,95 @@ pub fn update_jump_buffer(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifiers::MovementModifier;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn distance_timing_covers_the_distance() {
+        for downward_movement_multiplier in [1.0, 2.5] {
+            let puppeteer = Puppeteer {
+                jump_timing: JumpTiming::Distance(6.0),
+                downward_movement_multiplier,
+                ..default()
+            };
+            assert_close(puppeteer.jump_parameters().distance, 6.0);
+        }
+    }
+
+    #[test]
+    fn rise_fall_timing_matches_the_times() {
+        let puppeteer = Puppeteer {
+            jump_timing: JumpTiming::RiseFall {
+                rise: 0.4,
+                fall: 0.25,
+            },
+            ..default()
+        };
+        let parameters = puppeteer.jump_parameters();
+        assert_close(parameters.time_to_apex, 0.4);
+        assert_close(parameters.fall_time, 0.25);
+    }
+
+    #[test]
+    fn jump_reaches_the_height_at_the_apex() {
+        let puppeteer = Puppeteer::default();
+        let parameters = puppeteer.jump_parameters();
+        let gravity = puppeteer.gravity * parameters.gravity_scale;
+        assert_close(
+            parameters.jump_speed.powi(2) / (2.0 * gravity),
+            puppeteer.jump_height,
+        );
+        assert_close(parameters.jump_speed / gravity, parameters.time_to_apex);
+    }
+
+    #[test]
+    fn released_jump_stops_at_the_min_height() {
+        let puppeteer = Puppeteer {
+            jump_height: 2.0,
+            min_jump_height: 0.5,
+            ..default()
+        };
+        let parameters = puppeteer.jump_parameters();
+        let gravity = puppeteer.gravity * parameters.gravity_scale * parameters.cutoff_multiplier;
+        assert_close(
+            parameters.jump_speed.powi(2) / (2.0 * gravity),
+            puppeteer.min_jump_height,
+        );
+    }
+
+    #[test]
+    fn surfaces_scale_the_modified_acceleration() {
+        let puppeteer = Puppeteer {
+            acceleration: 10.0,
+            ..default()
+        };
+        let mut modifiers = MovementModifiers::default();
+        modifiers.insert(
+            "boost",
+            MovementModifier::add(ModifierStat::Acceleration, 10.0),
+        );
+        let velocity = puppeteer.horizontal_velocity(
+            Vec3::ZERO,
+            Vec3::X,
+            1.0,
+            Some(PuppetSurface {
+                acceleration_multiplier: 0.5,
+                ..default()
+            }),
+            Vec3::ZERO,
+            &modifiers,
+            0.01,
+        );
+        assert_close(velocity.x, (10.0 + 10.0) * 0.5 * 0.01);
+    }
+}
@@ -0,0 +1,370 @@
+use avian3d::prelude::{
+    Collider, ColliderAabb, ColliderOf, ComputedMass, Forces, Gravity, RigidBody, RigidBodyForces,
+    SpatialQuery, SpatialQueryFilter,
+};
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::puppet::{
+    DropThrough, Grounded, PlatformBodyQuery, Puppet, SurfaceData, cast_puppet_shape,
+    platform_velocity_at, puppet_filter,
+};
+
+/// Extra distance around a puppet in which moving bodies are checked for pushing it
+const PUSH_MARGIN: f32 = 0.5;
+
+/// How a puppet pushes dynamic rigid bodies it runs into or stands on.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct PushSettings {
+    /// Impulse per second applied to pushed bodies,
+    /// relative to [`Puppet::mass`] and the speed the puppet pushes with
+    pub strength: f32,
+    /// Bodies heavier than this can't be pushed
+    pub max_mass: f32,
+    /// Whether the puppet pushes bodies while it isn't grounded
+    pub push_while_airborne: bool,
+    /// Whether the puppet applies its weight to bodies it stands on
+    pub apply_weight: bool,
+}
+
+impl Default for PushSettings {
+    fn default() -> Self {
+        Self {
+            strength: 1.0,
+            max_mass: f32::INFINITY,
+            push_while_airborne: false,
+            apply_weight: false,
+        }
+    }
+}
+
+/// Marker component for rigid bodies that puppets can't push.
+#[derive(Reflect, Clone, Copy, Component, Debug, Default, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct NotPushable;
+
+/// How a puppet is kept apart from other puppets.
+///
+/// Puppets block each other while moving, but overlapping puppets, for example after spawning
+/// in a crowd, are pushed apart over a few ticks instead of being depenetrated.
+/// Puppets within [`Self::padding`] of a puppet with the same or a higher priority are pushed
+/// aside, so a player can slowly push through a crowd of NPCs.
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct SeparationSettings {
+    /// Puppets are only pushed aside by puppets with the same or a higher priority
+    pub priority: i32,
+    /// Extra distance kept to other puppets
+    pub padding: f32,
+    /// How fast overlapping puppets are pushed apart, relative to the overlap per second
+    pub strength: f32,
+}
+
+impl Default for SeparationSettings {
+    fn default() -> Self {
+        Self {
+            priority: 0,
+            padding: 0.05,
+            strength: 10.0,
+        }
+    }
+}
+
+/// A collider a puppet ran into while moving
+pub(crate) struct Push {
+    pub collider: Entity,
+    /// The movement of the puppet that hit the collider
+    pub displacement: Vec3,
+    /// The point where the puppet hit the collider
+    pub point: Vec3,
+}
+
+/// Queries used by [`move_puppet`](crate::puppet::move_puppet) to push dynamic bodies.
+#[derive(SystemParam)]
+pub struct PushParams<'w, 's> {
+    forces: Query<'w, 's, Forces>,
+    collider_query: Query<'w, 's, &'static ColliderOf>,
+    body_query: Query<'w, 's, (&'static ComputedMass, Has<NotPushable>)>,
+}
+
+impl PushParams<'_, '_> {
+    /// Applies an impulse to the body of the given collider, if the puppet is allowed to push it
+    fn apply_impulse(
+        &mut self,
+        settings: &PushSettings,
+        collider: Entity,
+        impulse: Vec3,
+        point: Vec3,
+    ) {
+        let body = self
+            .collider_query
+            .get(collider)
+            .map_or(collider, |collider_of| collider_of.body);
+        let Ok((mass, not_pushable)) = self.body_query.get(body) else {
+            return;
+        };
+        if not_pushable || mass.value() > settings.max_mass {
+            return;
+        }
+        if let Ok(mut forces) = self.forces.get_mut(body) {
+            forces.apply_linear_impulse_at_point(impulse, point);
+        }
+    }
+
+    /// Pushes the collider the puppet ran into.
+    /// The impulse per second is `speed * mass * strength`, which for a single tick
+    /// comes down to the displacement of the puppet.
+    pub(crate) fn push(&mut self, puppet: &Puppet, settings: &PushSettings, push: Push) {
+        let impulse = push.displacement * puppet.mass * settings.strength;
+        self.apply_impulse(settings, push.collider, impulse, push.point);
+    }
+}
+
+/// Message sent when a puppet is pushed into an obstacle it can't move out of.
+///
+/// Other puppets and colliders the puppet passes through never count as obstacles.
+#[derive(Message, Clone, Copy, Debug, PartialEq)]
+pub struct PuppetCrushed {
+    /// The crushed puppet
+    pub puppet: Entity,
+    /// The body pushing the puppet
+    pub pusher: Entity,
+    /// The obstacle the puppet is pushed into
+    pub obstacle: Entity,
+}
+
+/// How much of a pushing body's motion is passed on to a puppet with the given mass.
+///
+/// Kinematic bodies can't be stopped by a puppet and push it all the way.
+fn push_factor(puppet: &Puppet, rigid_body: &RigidBody, mass: Option<&ComputedMass>) -> f32 {
+    if rigid_body.is_kinematic() {
+        return 1.0;
+    }
+    let mass = mass.map_or(0.0, |mass| mass.value());
+    if mass + puppet.mass <= 0.0 {
+        return 0.0;
+    }
+    mass / (mass + puppet.mass)
+}
+
+/// Displaces puppets that are about to be hit by moving kinematic or dynamic rigid bodies.
+#[allow(clippy::complexity)]
+pub(crate) fn push_puppets(
+    mut puppet_query: Query<(
+        Entity,
+        &Puppet,
+        &Collider,
+        &ColliderAabb,
+        &mut Transform,
+        Option<&Grounded>,
+        Option<&DropThrough>,
+    )>,
+    collider_query: Query<&ColliderOf>,
+    body_query: PlatformBodyQuery,
+    mass_query: Query<(&RigidBody, Option<&ComputedMass>)>,
+    spatial_query: SpatialQuery,
+    surface_query: Query<SurfaceData>,
+    other_puppets: Query<(), With<Puppet>>,
+    mut crushed: MessageWriter<PuppetCrushed>,
+    time: Res<Time>,
+) {
+    for (entity, puppet, collider, aabb, mut transform, grounded, drop_through) in
+        puppet_query.iter_mut()
+    {
+        let query_filter = puppet_filter(entity, drop_through);
+        let candidates = spatial_query.aabb_intersections_with_aabb(ColliderAabb {
+            min: aabb.min - Vec3::splat(PUSH_MARGIN),
+            max: aabb.max + Vec3::splat(PUSH_MARGIN),
+        });
+
+        let mut push = Vec3::ZERO;
+        let mut pushers = Vec::new();
+        for candidate in candidates {
+            // The ground is handled by the moving platform logic
+            if candidate == entity || grounded.is_some_and(|grounded| grounded.0 == candidate) {
+                continue;
+            }
+            let Some(body_vel) = platform_velocity_at(
+                transform.translation,
+                candidate,
+                &collider_query,
+                &body_query,
+            ) else {
+                continue;
+            };
+            let Ok(direction) = Dir3::new(body_vel) else {
+                continue;
+            };
+
+            // Moving the puppet against the body's motion tells if the body reaches it this tick
+            let distance = body_vel.length() * time.delta_secs();
+            let Some(hit) = cast_puppet_shape(
+                &spatial_query,
+                collider,
+                transform.translation,
+                -direction,
+                distance + puppet.skin_thickness,
+                query_filter.clone(),
+                &surface_query,
+            ) else {
+                continue;
+            };
+            if hit.entity != candidate {
+                continue;
+            }
+
+            let body = collider_query
+                .get(candidate)
+                .map_or(candidate, |collider_of| collider_of.body);
+            let Ok((rigid_body, mass)) = mass_query.get(body) else {
+                continue;
+            };
+
+            let penetration = distance + puppet.skin_thickness - hit.distance;
+            push += direction * penetration * push_factor(puppet, rigid_body, mass);
+            pushers.push(candidate);
+        }
+
+        let Ok(direction) = Dir3::new(push) else {
+            continue;
+        };
+
+        let mut query_filter = query_filter;
+        query_filter
+            .excluded_entities
+            .extend(pushers.iter().copied());
+
+        let distance = push.length();
+        let mut moved = distance;
+        if let Some(hit) = cast_puppet_shape(
+            &spatial_query,
+            collider,
+            transform.translation,
+            direction,
+            distance + puppet.skin_thickness,
+            query_filter,
+            &surface_query,
+        ) {
+            moved = (hit.distance - puppet.skin_thickness).clamp(0.0, distance);
+            // Other puppets get out of the way on their own, so they don't crush the puppet
+            if distance - moved > puppet.skin_thickness && !other_puppets.contains(hit.entity) {
+                crushed.write(PuppetCrushed {
+                    puppet: entity,
+                    pusher: pushers[0],
+                    obstacle: hit.entity,
+                });
+            }
+        }
+
+        transform.translation += direction * moved;
+    }
+}
+
+/// Applies the weight of grounded puppets to the bodies they stand on.
+pub(crate) fn apply_weight(
+    puppet_query: Query<(&Puppet, &PushSettings, &Grounded, &Transform)>,
+    mut push_params: PushParams,
+    gravity: Res<Gravity>,
+    time: Res<Time>,
+) {
+    for (puppet, settings, grounded, transform) in puppet_query.iter() {
+        if !settings.apply_weight {
+            continue;
+        }
+        push_params.apply_impulse(
+            settings,
+            grounded.0,
+            gravity.0 * puppet.mass * time.delta_secs(),
+            transform.translation,
+        );
+    }
+}
+
+/// Pushes overlapping puppets apart, respecting their [`SeparationSettings::priority`].
+#[allow(clippy::complexity)]
+pub(crate) fn separate_puppets(
+    mut puppet_query: Query<(
+        Entity,
+        &Puppet,
+        &SeparationSettings,
+        &Collider,
+        &ColliderAabb,
+        &mut Transform,
+    )>,
+    spatial_query: SpatialQuery,
+    surface_query: Query<SurfaceData>,
+    time: Res<Time>,
+) {
+    let mut separations = Vec::new();
+    for (entity, _, settings, _, aabb, transform) in puppet_query.iter() {
+        let radius = (aabb.max - aabb.min).xz().max_element() * 0.5;
+        let mut separation = Vec3::ZERO;
+
+        for other in spatial_query.aabb_intersections_with_aabb(ColliderAabb {
+            min: aabb.min - Vec3::splat(settings.padding),
+            max: aabb.max + Vec3::splat(settings.padding),
+        }) {
+            if other == entity {
+                continue;
+            }
+            let Ok((_, _, other_settings, _, other_aabb, other_transform)) =
+                puppet_query.get(other)
+            else {
+                continue;
+            };
+
+            let share = match settings.priority.cmp(&other_settings.priority) {
+                std::cmp::Ordering::Greater => continue,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 1.0,
+            };
+
+            let other_radius = (other_aabb.max - other_aabb.min).xz().max_element() * 0.5;
+            let offset = (transform.translation - other_transform.translation).xz();
+            let overlap = radius + other_radius + settings.padding - offset.length();
+            if overlap <= 0.0 {
+                continue;
+            }
+
+            // Puppets at the exact same position are separated by their entity order
+            let direction = offset.try_normalize().unwrap_or(if entity < other {
+                Vec2::X
+            } else {
+                Vec2::NEG_X
+            });
+            separation += Vec3::new(direction.x, 0.0, direction.y)
+                * overlap
+                * share
+                * (settings.strength * time.delta_secs()).min(1.0);
+        }
+
+        if separation != Vec3::ZERO {
+            separations.push((entity, separation));
+        }
+    }
+
+    for (entity, separation) in separations {
+        let Ok((_, puppet, _, collider, _, mut transform)) = puppet_query.get_mut(entity) else {
+            continue;
+        };
+        let Ok(direction) = Dir3::new(separation) else {
+            continue;
+        };
+
+        let distance = separation.length();
+        let moved = cast_puppet_shape(
+            &spatial_query,
+            collider,
+            transform.translation,
+            direction,
+            distance + puppet.skin_thickness,
+            SpatialQueryFilter::default().with_excluded_entities([entity]),
+            &surface_query,
+        )
+        .map_or(distance, |hit| {
+            (hit.distance - puppet.skin_thickness).clamp(0.0, distance)
+        });
+
+        transform.translation += direction * moved;
+    }
+}
@@ -0,0 +1,264 @@
+use avian3d::prelude::{Collider, SpatialQuery, SpatialQueryFilter};
+use bevy::prelude::*;
+
+use crate::{
+    external::ExternalVelocity,
+    modifiers::{ModifierStat, MovementModifiers},
+    puppet::{Puppet, SurfaceData, cast_puppet_shape},
+    puppeteer::{Puppeteer, PuppeteerInput},
+};
+#[cfg(feature = "gizmos")]
+use crate::{
+    puppet::Grounded,
+    puppeteer::{AirJumpCount, CoyoteTime},
+};
+
+/// Input used while predicting a jump with [`predict_jump`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JumpInput {
+    /// Direction the puppeteer moves in during the jump
+    pub move_direction: Vec3,
+    pub speed_multiplier: f32,
+    /// How long the jump is held, `None` to hold it for the whole jump
+    pub hold_time: Option<f32>,
+}
+
+impl JumpInput {
+    /// Holds the jump and keeps moving like the given input.
+    /// The input is reset after every tick, so this has to be used before [`PuppeteerSet::Compute`](crate::PuppeteerSet::Compute)
+    pub fn from_input(input: &PuppeteerInput) -> Self {
+        Self {
+            move_direction: input.move_direction,
+            speed_multiplier: input.speed_multiplier,
+            hold_time: None,
+        }
+    }
+
+    /// Holds the jump and keeps the current velocity of the puppet
+    pub fn keep_velocity(
+        puppeteer: &Puppeteer,
+        puppet: &Puppet,
+        modifiers: &MovementModifiers,
+    ) -> Self {
+        let velocity = puppet.target_velocity * Vec3::new(1.0, 0.0, 1.0);
+        let max_speed = modifiers.apply(ModifierStat::MaxSpeed, puppeteer.max_speed);
+        Self {
+            move_direction: velocity.normalize_or_zero(),
+            speed_multiplier: if max_speed > 0.0 {
+                velocity.length() / max_speed
+            } else {
+                0.0
+            },
+            hold_time: None,
+        }
+    }
+}
+
+/// Where a predicted jump hits the ground.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JumpLanding {
+    pub position: Vec3,
+    pub normal: Vec3,
+    /// The collider the puppet lands on
+    pub entity: Entity,
+    /// Time after the jump starts
+    pub time: f32,
+}
+
+/// A jump simulated by [`predict_jump`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JumpPrediction {
+    /// Position of the puppet at every simulated tick
+    pub points: Vec<Vec3>,
+    /// `None` if nothing was hit within the simulated time
+    pub landing: Option<JumpLanding>,
+}
+
+/// Simulates a jump starting at `position` with the same rules as the puppeteer systems and
+/// returns the path of the puppet until it hits something. Colliders the puppet passes through,
+/// like sensors or the underside of one-way platforms, are skipped.
+///
+/// `jump_index` picks the [`JumpProfile`](crate::puppeteer::JumpProfile) of the jump, see
+/// [`Puppeteer::next_jump_index`]. `time_step` should be the fixed timestep the puppeteer runs with.
+#[allow(clippy::too_many_arguments)]
+pub fn predict_jump(
+    spatial_query: &SpatialQuery,
+    query_filter: &SpatialQueryFilter,
+    surface_query: &Query<SurfaceData>,
+    collider: &Collider,
+    position: Vec3,
+    puppeteer: &Puppeteer,
+    puppet: &Puppet,
+    external: &ExternalVelocity,
+    modifiers: &MovementModifiers,
+    jump_index: u32,
+    input: JumpInput,
+    time_step: f32,
+    max_time: f32,
+) -> JumpPrediction {
+    let mut prediction = JumpPrediction {
+        points: vec![position],
+        landing: None,
+    };
+
+    // The vertical external velocity is moved to the gravity velocity before jumping
+    let mut external_velocity = external.velocity * Vec3::new(1.0, 0.0, 1.0);
+    let gravity_velocity = external.launch(puppet.gravity_velocity);
+
+    let profile = puppeteer.jump_profile(jump_index).unwrap_or_default();
+    let (mut gravity_velocity, velocity) =
+        puppeteer.jump_velocity(profile, gravity_velocity, puppet.target_velocity, modifiers);
+    let mut velocity = velocity * Vec3::new(1.0, 0.0, 1.0);
+    let mut position = position;
+    let mut time = 0.0;
+
+    while time < max_time {
+        velocity = puppeteer.horizontal_velocity(
+            velocity,
+            input.move_direction,
+            input.speed_multiplier,
+            None,
+            Vec3::ZERO,
+            modifiers,
+            time_step,
+        );
+
+        let is_jumping = input.hold_time.is_none_or(|hold_time| time < hold_time);
+        let gravity_scale = modifiers.apply(
+            ModifierStat::Gravity,
+            puppeteer.gravity_scale(
+                puppeteer.gravity_multiplier(
+                    gravity_velocity,
+                    is_jumping,
+                    modifiers.apply(ModifierStat::JumpHeight, profile.height),
+                ) * profile.gravity_multiplier,
+            ),
+        );
+        gravity_velocity -= puppeteer.gravity * gravity_scale * time_step;
+        gravity_velocity = gravity_velocity.max(-puppeteer.max_fall_speed);
+        if let Some(max_fall_speed) = puppet.max_fall_speed {
+            gravity_velocity = gravity_velocity.max(-max_fall_speed);
+        }
+        time += time_step;
+
+        external_velocity *= (-external.air_drag * time_step).exp();
+        let movement = (velocity + external_velocity + Vec3::Y * gravity_velocity) * time_step;
+        let Ok(direction) = Dir3::new(movement) else {
+            prediction.points.push(position);
+            continue;
+        };
+
+        if let Some(hit) = cast_puppet_shape(
+            spatial_query,
+            collider,
+            position,
+            direction,
+            movement.length() + puppet.skin_thickness,
+            query_filter.clone(),
+            surface_query,
+        ) {
+            position += direction * (hit.distance - puppet.skin_thickness).max(0.0);
+            prediction.points.push(position);
+            prediction.landing = Some(JumpLanding {
+                position,
+                normal: hit.normal1,
+                entity: hit.entity,
+                time,
+            });
+            break;
+        }
+
+        position += movement;
+        prediction.points.push(position);
+    }
+
+    prediction
+}
+
+/// Add this component to a [`Puppeteer`] to draw where a jump with its current velocity would land.
+///
+/// Requires the `gizmos` feature and a [`GizmoPlugin`](bevy::gizmos::GizmoPlugin).
+#[cfg(feature = "gizmos")]
+#[derive(Reflect, Clone, Copy, Component, Debug, PartialEq)]
+#[reflect(Debug, Component, Default, PartialEq)]
+pub struct JumpPredictionGizmo {
+    pub color: Color,
+    /// Maximum simulated time of the jump
+    pub max_time: f32,
+}
+
+#[cfg(feature = "gizmos")]
+impl Default for JumpPredictionGizmo {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            max_time: 3.0,
+        }
+    }
+}
+
+#[cfg(feature = "gizmos")]
+#[allow(clippy::complexity)]
+pub(crate) fn draw_jump_prediction(
+    query: Query<(
+        Entity,
+        &JumpPredictionGizmo,
+        &Puppeteer,
+        &Puppet,
+        &ExternalVelocity,
+        &MovementModifiers,
+        &Collider,
+        &Transform,
+        Has<Grounded>,
+        Option<&CoyoteTime>,
+        Option<&AirJumpCount>,
+    )>,
+    spatial_query: SpatialQuery,
+    surface_query: Query<SurfaceData>,
+    fixed_time: Res<Time<Fixed>>,
+    mut gizmos: Gizmos,
+) {
+    for (
+        entity,
+        gizmo,
+        puppeteer,
+        puppet,
+        external,
+        modifiers,
+        collider,
+        transform,
+        is_grounded,
+        coyote_time,
+        air_jump_count,
+    ) in query.iter()
+    {
+        let Some(jump_index) = puppeteer.next_jump_index(is_grounded, coyote_time, air_jump_count)
+        else {
+            continue;
+        };
+        let prediction = predict_jump(
+            &spatial_query,
+            &SpatialQueryFilter::default().with_excluded_entities([entity]),
+            &surface_query,
+            collider,
+            transform.translation,
+            puppeteer,
+            puppet,
+            external,
+            modifiers,
+            jump_index,
+            JumpInput::keep_velocity(puppeteer, puppet, modifiers),
+            fixed_time.timestep().as_secs_f32(),
+            gizmo.max_time,
+        );
+
+        gizmos.linestrip(prediction.points, gizmo.color);
+        if let Some(landing) = prediction.landing {
+            gizmos.sphere(
+                Isometry3d::from_translation(landing.position),
+                0.1,
+                gizmo.color,
+            );
+        }
+    }
+}
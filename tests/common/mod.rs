@@ -0,0 +1,49 @@
+//! Setup shared by the integration tests
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{app::Plugins, prelude::*, scene::ScenePlugin, time::TimeUpdateStrategy};
+use puppeteer::navmesh::NavAgent;
+
+/// Length of a fixed tick
+pub const TIME_STEP: f32 = 1.0 / 64.0;
+/// Half height of the default puppet collider, `Collider::capsule(0.25, 1.20)`
+pub const PUPPET_HALF_HEIGHT: f32 = 0.85;
+/// Radius of the default puppet collider
+pub const PUPPET_RADIUS: f32 = 0.25;
+
+/// Creates a headless app with physics and the given plugins, stepping one tick per update
+pub fn create_app<M>(plugins: impl Plugins<M>) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        AssetPlugin::default(),
+        ScenePlugin,
+        PhysicsPlugins::default(),
+    ))
+    .add_plugins(plugins)
+    .init_asset::<Mesh>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        TIME_STEP as f64,
+    )));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// An agent with the size of the default puppet
+pub fn agent() -> NavAgent {
+    NavAgent {
+        radius: PUPPET_RADIUS,
+        height: PUPPET_HALF_HEIGHT * 2.0,
+        step_height: 0.3,
+        max_slope_angle: 45.0,
+        max_speed: 7.0,
+        jump_height: 1.0,
+        time_to_jump_apex: 0.3,
+        downward_movement_multiplier: 1.0,
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use avian3d::prelude::*;
+use bevy::{ecs::message::MessageCursor, prelude::*};
+use common::PUPPET_HALF_HEIGHT;
+use puppeteer::{
+    PuppeteerPlugin,
+    puppeteer::{FallImpact, Puppeteer, PuppeteerInput},
+};
+
+const LEDGE_HEIGHT: f32 = 1.0;
+
+#[test]
+fn walking_off_a_ledge_falls_from_rest() {
+    let mut app = common::create_app(PuppeteerPlugin);
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Transform::from_xyz(0.0, -0.5, 0.0),
+    ));
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(4.0, LEDGE_HEIGHT, 4.0),
+        Transform::from_xyz(0.0, LEDGE_HEIGHT * 0.5, 0.0),
+    ));
+    // Land on the ledge from four times its height
+    let puppeteer = app
+        .world_mut()
+        .spawn((
+            Puppeteer::default(),
+            Transform::from_xyz(0.0, LEDGE_HEIGHT * 5.0 + PUPPET_HALF_HEIGHT, 0.0),
+        ))
+        .id();
+    let mut cursor = MessageCursor::<FallImpact>::default();
+    let mut impacts = Vec::new();
+
+    for tick in 0..192 {
+        // Walk off the ledge once landed
+        if tick >= 128 {
+            let mut input = app
+                .world_mut()
+                .get_mut::<PuppeteerInput>(puppeteer)
+                .unwrap();
+            input.speed_multiplier = 1.0;
+            input.move_amount(Vec3::X);
+        }
+        app.update();
+        impacts.extend(
+            cursor
+                .read(app.world().resource::<Messages<FallImpact>>())
+                .copied(),
+        );
+    }
+
+    assert_eq!(impacts.len(), 2, "{impacts:?}");
+    assert!(
+        impacts[1].speed < impacts[0].speed * 0.75,
+        "walking off the ledge landed with {}, dropping onto it with {}",
+        impacts[1].speed,
+        impacts[0].speed
+    );
+}
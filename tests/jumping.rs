@@ -0,0 +1,62 @@
+mod common;
+
+use bevy::prelude::*;
+use puppeteer::{
+    PuppeteerPlugin,
+    puppeteer::{AirJumpCount, JumpProfile, JumpTiming, Puppeteer, PuppeteerInput},
+};
+
+fn input(app: &mut App, entity: Entity) -> Mut<'_, PuppeteerInput> {
+    app.world_mut().get_mut::<PuppeteerInput>(entity).unwrap()
+}
+
+fn height(app: &App, entity: Entity) -> f32 {
+    app.world().get::<Transform>(entity).unwrap().translation.y
+}
+
+#[test]
+fn tapped_air_jump_stops_at_the_min_height() {
+    let mut app = common::create_app(PuppeteerPlugin);
+    // A slow jump, so the ticks until the release takes effect barely add height
+    let puppeteer = Puppeteer {
+        jump_height: 1.0,
+        jump_timing: JumpTiming::Apex(1.0),
+        min_jump_height: 0.5,
+        air_jumps: vec![JumpProfile {
+            height: 2.0,
+            ..default()
+        }],
+        ..default()
+    };
+    let min_jump_height = puppeteer.min_jump_height;
+    let entity = app
+        .world_mut()
+        .spawn((puppeteer, Transform::from_xyz(0.0, 20.0, 0.0)))
+        .id();
+
+    // Fall until the coyote time is over
+    for _ in 0..16 {
+        app.update();
+    }
+
+    let start = height(&app, entity);
+    input(&mut app, entity).start_jump();
+    app.update();
+    assert_eq!(
+        app.world().get::<AirJumpCount>(entity).map(|jumps| jumps.0),
+        Some(1),
+        "the puppeteer didn't air jump"
+    );
+    input(&mut app, entity).stop_jump();
+
+    let mut apex = height(&app, entity);
+    for _ in 0..64 {
+        app.update();
+        apex = apex.max(height(&app, entity));
+    }
+    let jump_height = apex - start;
+    assert!(
+        (jump_height - min_jump_height).abs() < 0.1,
+        "the tapped air jump reached {jump_height}"
+    );
+}
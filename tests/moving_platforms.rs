@@ -0,0 +1,187 @@
+mod common;
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use common::{PUPPET_HALF_HEIGHT, PUPPET_RADIUS, TIME_STEP};
+use puppeteer::{
+    PuppeteerSet,
+    puppet::{Grounded, Puppet, PuppetPlugin},
+};
+
+const PLATFORM_HALF_HEIGHT: f32 = 0.25;
+const TICKS: usize = 64;
+
+#[derive(Component)]
+struct Elevator(f32);
+
+fn move_elevators(mut query: Query<(&mut Transform, &Elevator)>, time: Res<Time>) {
+    for (mut transform, elevator) in &mut query {
+        transform.translation.y += elevator.0 * time.delta_secs();
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app(PuppetPlugin);
+    app.configure_sets(
+        FixedPostUpdate,
+        (
+            PuppeteerSet::Prepare,
+            PuppeteerSet::Compute,
+            PuppeteerSet::Move,
+        )
+            .chain()
+            .before(PhysicsSystems::Prepare),
+    )
+    .add_systems(FixedUpdate, move_elevators);
+    app
+}
+
+/// Spawns a puppet standing on a kinematic platform and lets it settle
+fn spawn_puppet_on_platform(app: &mut App) -> (Entity, Entity) {
+    let platform = app
+        .world_mut()
+        .spawn((
+            RigidBody::Kinematic,
+            Collider::cuboid(4.0, PLATFORM_HALF_HEIGHT * 2.0, 4.0),
+            Transform::default(),
+        ))
+        .id();
+    let puppet = Puppet::default();
+    let puppet = app
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(
+                0.0,
+                PLATFORM_HALF_HEIGHT + PUPPET_HALF_HEIGHT + puppet.skin_thickness,
+                0.0,
+            ),
+            puppet,
+        ))
+        .id();
+
+    for _ in 0..8 {
+        app.update();
+    }
+    assert!(
+        app.world().get::<Grounded>(puppet).is_some(),
+        "puppet didn't land on the platform"
+    );
+
+    (puppet, platform)
+}
+
+/// Checks that the puppet stands on top of the platform
+fn assert_on_platform(app: &App, puppet: Entity, platform: Entity, tick: usize) {
+    let skin_thickness = app.world().get::<Puppet>(puppet).unwrap().skin_thickness;
+
+    assert_eq!(
+        app.world()
+            .get::<Grounded>(puppet)
+            .map(|grounded| grounded.0),
+        Some(platform),
+        "puppet lost the platform on tick {tick}"
+    );
+
+    let puppet_bottom =
+        app.world().get::<Transform>(puppet).unwrap().translation.y - PUPPET_HALF_HEIGHT;
+    let platform_top = app
+        .world()
+        .get::<Transform>(platform)
+        .unwrap()
+        .translation
+        .y
+        + PLATFORM_HALF_HEIGHT;
+    let gap = puppet_bottom - platform_top;
+    assert!(
+        gap >= -0.001,
+        "puppet overlaps the platform by {} on tick {tick}",
+        -gap
+    );
+    assert!(
+        gap <= skin_thickness * 2.0,
+        "puppet is {gap} above the platform on tick {tick}"
+    );
+}
+
+/// Runs the app and checks that the puppet stays on top of the platform every tick
+fn assert_carried(app: &mut App, puppet: Entity, platform: Entity) {
+    for tick in 0..TICKS {
+        app.update();
+        assert_on_platform(app, puppet, platform, tick);
+    }
+}
+
+#[test]
+fn carried_by_fast_rising_platform() {
+    let mut app = create_app();
+    let (puppet, platform) = spawn_puppet_on_platform(&mut app);
+
+    app.world_mut()
+        .entity_mut(platform)
+        .insert(LinearVelocity(Vec3::Y * 20.0));
+    assert_carried(&mut app, puppet, platform);
+}
+
+#[test]
+fn carried_by_fast_sinking_platform() {
+    let mut app = create_app();
+    let (puppet, platform) = spawn_puppet_on_platform(&mut app);
+
+    app.world_mut()
+        .entity_mut(platform)
+        .insert(LinearVelocity(Vec3::NEG_Y * 20.0));
+    assert_carried(&mut app, puppet, platform);
+}
+
+#[test]
+fn carried_by_transform_animated_platform() {
+    let mut app = create_app();
+    let (puppet, platform) = spawn_puppet_on_platform(&mut app);
+
+    app.world_mut().entity_mut(platform).insert(Elevator(15.0));
+    assert_carried(&mut app, puppet, platform);
+
+    app.world_mut().entity_mut(platform).insert(Elevator(-15.0));
+    assert_carried(&mut app, puppet, platform);
+}
+
+#[test]
+fn carried_while_walking_into_a_wall() {
+    const WALL_X: f32 = 1.0;
+
+    let mut app = create_app();
+    // Two fixed ticks per update
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        (TIME_STEP * 2.0) as f64,
+    )));
+    let (puppet, platform) = spawn_puppet_on_platform(&mut app);
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(1.0, 400.0, 4.0),
+        Transform::from_xyz(WALL_X + 0.5, 0.0, 0.0),
+    ));
+    app.world_mut()
+        .get_mut::<Puppet>(puppet)
+        .unwrap()
+        .target_velocity = Vec3::X * 5.0;
+
+    for velocity in [20.0, -20.0] {
+        app.world_mut()
+            .entity_mut(platform)
+            .insert(LinearVelocity(Vec3::Y * velocity));
+        for tick in 0..TICKS {
+            app.update();
+            assert_on_platform(&app, puppet, platform, tick);
+
+            let puppet_side =
+                app.world().get::<Transform>(puppet).unwrap().translation.x + PUPPET_RADIUS;
+            assert!(
+                puppet_side <= WALL_X + 0.001,
+                "puppet is {} inside the wall on tick {tick}",
+                puppet_side - WALL_X
+            );
+        }
+    }
+}
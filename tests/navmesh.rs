@@ -0,0 +1,356 @@
+mod common;
+
+use avian3d::prelude::*;
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use common::agent;
+use puppeteer::{
+    navmesh::{NavMesh, NavMeshSettings},
+    puppet::{NoStep, NotWalkable, PuppetSurface, SurfaceData},
+};
+
+const CELL_SIZE: f32 = 0.5;
+
+/// Spawns a static box with the given bounds
+fn spawn_box(app: &mut App, min: Vec3, max: Vec3) -> Entity {
+    let size = max - min;
+    app.world_mut()
+        .spawn((
+            RigidBody::Static,
+            Collider::cuboid(size.x, size.y, size.z),
+            Transform::from_translation((min + max) * 0.5),
+        ))
+        .id()
+}
+
+/// Spawns a static wedge rising from `height` 0 at `x` to `height` at `x + length`
+fn spawn_wedge(app: &mut App, x: f32, length: f32, height: f32, z: [f32; 2]) -> Entity {
+    let points = [
+        Vec3::new(x, 0.0, z[0]),
+        Vec3::new(x, 0.0, z[1]),
+        Vec3::new(x + length, 0.0, z[0]),
+        Vec3::new(x + length, 0.0, z[1]),
+        Vec3::new(x + length, height, z[0]),
+        Vec3::new(x + length, height, z[1]),
+    ];
+    app.world_mut()
+        .spawn((
+            RigidBody::Static,
+            Collider::convex_hull(points.to_vec()).unwrap(),
+            Transform::default(),
+        ))
+        .id()
+}
+
+/// Lets the physics pick up the colliders and bakes the area around the origin
+fn bake(app: &mut App) -> NavMesh {
+    for _ in 0..4 {
+        app.update();
+    }
+    let settings = NavMeshSettings {
+        agent: agent(),
+        min: Vec3::new(-8.0, -1.0, -5.0),
+        max: Vec3::new(8.0, 5.0, 5.0),
+        cell_size: CELL_SIZE,
+        filter: SpatialQueryFilter::default(),
+    };
+    app.world_mut()
+        .run_system_once(
+            move |spatial_query: SpatialQuery, surface_query: Query<SurfaceData>| {
+                NavMesh::bake(&spatial_query, &surface_query, &settings)
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn walks_up_steps() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -2.0),
+        Vec3::new(6.0, 0.0, 2.0),
+    );
+    spawn_box(
+        &mut app,
+        Vec3::new(0.0, 0.0, -2.0),
+        Vec3::new(6.0, 0.25, 2.0),
+    );
+    let nav_mesh = bake(&mut app);
+
+    let path = nav_mesh
+        .find_path(Vec3::new(-4.0, 0.0, 0.0), Vec3::new(4.0, 0.25, 0.0))
+        .expect("the step is walkable");
+    assert!(path.iter().all(|waypoint| !waypoint.jump), "{path:?}");
+    assert!(
+        (path.last().unwrap().position.y - 0.25).abs() < 0.01,
+        "{path:?}"
+    );
+}
+
+#[test]
+fn walks_up_slopes_but_not_too_high_steps() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -5.0),
+        Vec3::new(6.0, 0.0, 5.0),
+    );
+    // A step lower than the max slope angle rises over a cell, but higher than the step height
+    spawn_box(
+        &mut app,
+        Vec3::new(0.0, 0.0, -4.0),
+        Vec3::new(6.0, 0.35, -1.0),
+    );
+    // About 39 degrees, rising more than the step height per cell
+    spawn_wedge(&mut app, 0.0, 2.0, 1.6, [1.0, 4.0]);
+    let nav_mesh = bake(&mut app);
+
+    for cell in nav_mesh
+        .cells
+        .iter()
+        .filter(|cell| cell.position.y < 0.1 && cell.position.z < 0.0)
+    {
+        for link in &cell.links {
+            let target = nav_mesh.cells[link.cell].position;
+            assert!(
+                target.y < 0.1 || link.jump,
+                "walks from {} up to {target}",
+                cell.position
+            );
+        }
+    }
+
+    let path = nav_mesh
+        .find_path(Vec3::new(-4.0, 0.0, 2.5), Vec3::new(1.75, 1.4, 2.5))
+        .expect("the slope is walkable");
+    assert!(path.iter().all(|waypoint| !waypoint.jump), "{path:?}");
+    assert!(path.last().unwrap().position.y > 1.0, "{path:?}");
+}
+
+#[test]
+fn skips_steep_slopes() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -5.0),
+        Vec3::new(6.0, 0.0, 5.0),
+    );
+    // About 63 degrees
+    spawn_wedge(&mut app, -1.0, 2.0, 4.0, [-4.0, -1.0]);
+    // About 17 degrees
+    spawn_wedge(&mut app, -1.0, 2.0, 0.6, [1.0, 4.0]);
+    let nav_mesh = bake(&mut app);
+
+    let on_slope = |z: f32| {
+        nav_mesh
+            .cells
+            .iter()
+            .any(|cell| cell.position.y > 0.1 && (cell.position.z - z).abs() < 1.5)
+    };
+    assert!(!on_slope(-2.5), "steep slope was baked");
+    assert!(on_slope(2.5), "gentle slope wasn't baked");
+}
+
+#[test]
+fn uses_surface_slope_overrides() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -5.0),
+        Vec3::new(6.0, 0.0, 5.0),
+    );
+    // About 31 degrees, too steep for this surface
+    let gentle = spawn_wedge(&mut app, -1.0, 2.0, 1.2, [-4.0, -1.0]);
+    app.world_mut().entity_mut(gentle).insert(PuppetSurface {
+        max_slope_angle: Some(20.0),
+        ..default()
+    });
+    // About 56 degrees, walkable on this surface
+    let steep = spawn_wedge(&mut app, -1.0, 2.0, 3.0, [1.0, 4.0]);
+    app.world_mut().entity_mut(steep).insert(PuppetSurface {
+        max_slope_angle: Some(60.0),
+        ..default()
+    });
+    let nav_mesh = bake(&mut app);
+
+    assert!(
+        nav_mesh
+            .cells
+            .iter()
+            .all(|cell| cell.position.y < 0.1 || cell.position.z > 0.0),
+        "the slope steeper than its override was baked"
+    );
+    let path = nav_mesh
+        .find_path(Vec3::new(-4.0, 0.0, 2.5), Vec3::new(0.75, 2.6, 2.5))
+        .expect("the steep slope is walkable");
+    assert!(path.iter().all(|waypoint| !waypoint.jump), "{path:?}");
+    assert!(path.last().unwrap().position.y > 2.0, "{path:?}");
+}
+
+#[test]
+fn skips_not_walkable_floors() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -5.0),
+        Vec3::new(6.0, 0.0, 5.0),
+    );
+    // Low enough to step onto if it were walkable
+    let prop_min = Vec2::new(-1.0, -2.0);
+    let prop_max = Vec2::new(1.0, 2.0);
+    let prop = spawn_box(
+        &mut app,
+        Vec3::new(prop_min.x, 0.0, prop_min.y),
+        Vec3::new(prop_max.x, 0.25, prop_max.y),
+    );
+    app.world_mut().entity_mut(prop).insert(NotWalkable);
+    let nav_mesh = bake(&mut app);
+
+    // Neither on top of the prop nor on the floor below it
+    for cell in &nav_mesh.cells {
+        let position = cell.position.xz();
+        assert!(
+            (position - position.clamp(prop_min, prop_max)).length() >= agent().radius - 0.01,
+            "baked a cell at {} on the prop",
+            cell.position
+        );
+    }
+}
+
+#[test]
+fn doesnt_step_onto_no_step() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -5.0),
+        Vec3::new(6.0, 0.0, 5.0),
+    );
+    let no_step = spawn_box(
+        &mut app,
+        Vec3::new(0.0, 0.0, -4.0),
+        Vec3::new(6.0, 0.25, -1.0),
+    );
+    app.world_mut().entity_mut(no_step).insert(NoStep);
+    spawn_box(
+        &mut app,
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(6.0, 0.25, 4.0),
+    );
+    let nav_mesh = bake(&mut app);
+
+    let mut steps = 0;
+    for cell in nav_mesh.cells.iter().filter(|cell| cell.position.y < 0.1) {
+        for link in cell.links.iter().filter(|link| !link.jump) {
+            let target = nav_mesh.cells[link.cell].position;
+            if target.y > 0.1 {
+                assert!(target.z > 0.0, "steps from {} onto {target}", cell.position);
+                steps += 1;
+            }
+        }
+    }
+    assert!(steps > 0, "doesn't step onto the regular step");
+}
+
+#[test]
+fn links_keep_clear_of_wall_corners() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-4.0, -1.0, -4.0),
+        Vec3::new(4.0, 0.0, 4.0),
+    );
+    let wall_min = Vec2::new(-0.25, -4.0);
+    let wall_max = Vec2::new(0.25, 0.0);
+    spawn_box(
+        &mut app,
+        Vec3::new(wall_min.x, 0.0, wall_min.y),
+        Vec3::new(wall_max.x, 2.0, wall_max.y),
+    );
+    let nav_mesh = bake(&mut app);
+
+    let distance_to_wall = |point: Vec2| (point - point.clamp(wall_min, wall_max)).length();
+    // Cells on top of the wall are skipped, only links on the floor can cut the corner
+    for cell in nav_mesh.cells.iter().filter(|cell| cell.position.y < 0.1) {
+        for link in &cell.links {
+            let from = cell.position.xz();
+            let to = nav_mesh.cells[link.cell].position.xz();
+            for step in 0..=10 {
+                let point = from.lerp(to, step as f32 / 10.0);
+                assert!(
+                    distance_to_wall(point) >= agent().radius - 0.01,
+                    "link from {from} to {to} cuts the wall"
+                );
+            }
+        }
+    }
+
+    let path = nav_mesh
+        .find_path(Vec3::new(-2.0, 0.0, -2.0), Vec3::new(2.0, 0.0, -2.0))
+        .expect("the path leads around the wall");
+    assert!(
+        path.iter().any(|waypoint| waypoint.position.z > 0.0),
+        "{path:?}"
+    );
+}
+
+#[test]
+fn jumps_over_gaps() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -2.0),
+        Vec3::new(-1.0, 0.0, 2.0),
+    );
+    spawn_box(
+        &mut app,
+        Vec3::new(1.0, -1.0, -2.0),
+        Vec3::new(6.0, 0.0, 2.0),
+    );
+    // Sensors neither count as floor nor block the jump
+    let sensor = spawn_box(
+        &mut app,
+        Vec3::new(-1.0, -1.0, -2.0),
+        Vec3::new(1.0, 3.0, 2.0),
+    );
+    app.world_mut().entity_mut(sensor).insert(Sensor);
+    let nav_mesh = bake(&mut app);
+
+    assert!(
+        nav_mesh
+            .cells
+            .iter()
+            .all(|cell| cell.position.x.abs() > 1.0),
+        "the sensor was baked as floor"
+    );
+    let path = nav_mesh
+        .find_path(Vec3::new(-4.0, 0.0, 0.0), Vec3::new(4.0, 0.0, 0.0))
+        .expect("the gap can be jumped");
+    assert!(path.iter().any(|waypoint| waypoint.jump), "{path:?}");
+}
+
+#[test]
+fn doesnt_jump_into_ceilings() {
+    let mut app = common::create_app(());
+    spawn_box(
+        &mut app,
+        Vec3::new(-6.0, -1.0, -2.0),
+        Vec3::new(-1.0, 0.0, 2.0),
+    );
+    spawn_box(
+        &mut app,
+        Vec3::new(1.0, -1.0, -2.0),
+        Vec3::new(6.0, 0.0, 2.0),
+    );
+    // Above the apex of the jump, but low enough to hit the head of the agent
+    spawn_box(
+        &mut app,
+        Vec3::new(-0.5, 1.5, -2.0),
+        Vec3::new(0.5, 3.0, 2.0),
+    );
+    let nav_mesh = bake(&mut app);
+
+    assert_eq!(
+        nav_mesh.find_path(Vec3::new(-4.0, 0.0, 0.0), Vec3::new(4.0, 0.0, 0.0)),
+        None
+    );
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use avian3d::prelude::*;
+use bevy::{ecs::message::MessageCursor, prelude::*};
+use common::{PUPPET_HALF_HEIGHT, TIME_STEP};
+use puppeteer::{
+    PuppeteerPlugin,
+    puppet::{NotWalkable, OneWayPlatform},
+    puppeteer::{FallImpact, Puppeteer, PuppeteerInput},
+};
+
+const PROP_HEIGHT: f32 = 0.5;
+
+/// Reads the fall impacts sent since the last call
+fn fall_impacts(app: &App, cursor: &mut MessageCursor<FallImpact>) -> Vec<FallImpact> {
+    cursor
+        .read(app.world().resource::<Messages<FallImpact>>())
+        .copied()
+        .collect()
+}
+
+#[test]
+fn slides_off_flat_not_walkable_prop() {
+    const PROP_SIZE: f32 = 4.0;
+
+    let mut app = common::create_app(PuppeteerPlugin);
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(20.0, 1.0, 20.0),
+        Transform::from_xyz(0.0, -0.5, 0.0),
+    ));
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(PROP_SIZE, PROP_HEIGHT, PROP_SIZE),
+        Transform::from_xyz(0.0, PROP_HEIGHT * 0.5, 0.0),
+        NotWalkable,
+    ));
+    let puppeteer = app
+        .world_mut()
+        .spawn((
+            Puppeteer::default(),
+            Transform::from_xyz(0.5, PROP_HEIGHT + PUPPET_HALF_HEIGHT + 0.1, 0.0),
+        ))
+        .id();
+    let mut cursor = MessageCursor::<FallImpact>::default();
+
+    // The slide is slow and only falls from the height of the prop
+    let mut previous = app.world().get::<Transform>(puppeteer).unwrap().translation;
+    let mut impacts = Vec::new();
+    for tick in 0..512 {
+        app.update();
+
+        let translation = app.world().get::<Transform>(puppeteer).unwrap().translation;
+        let sideways_speed = (translation - previous).xz().length() / TIME_STEP;
+        assert!(
+            sideways_speed < 2.0,
+            "puppeteer slides with {sideways_speed} on tick {tick}"
+        );
+        impacts.extend(fall_impacts(&app, &mut cursor));
+        previous = translation;
+    }
+
+    let translation = app.world().get::<Transform>(puppeteer).unwrap().translation;
+    assert!(
+        translation.x.abs().max(translation.z.abs()) > PROP_SIZE * 0.5,
+        "puppeteer is still on the prop at {translation}"
+    );
+    assert!(
+        (translation.y - PUPPET_HALF_HEIGHT).abs() < 0.1,
+        "puppeteer didn't land on the floor, it's at {translation}"
+    );
+    assert!(
+        impacts.iter().all(|impact| impact.speed < 8.0),
+        "landed too hard after sliding off the prop: {impacts:?}"
+    );
+}
+
+#[test]
+fn walks_up_one_way_ramp() {
+    const LENGTH: f32 = 10.0;
+    let slope = 25.0_f32.to_radians().tan();
+
+    let mut app = common::create_app(PuppeteerPlugin);
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(40.0, 1.0, 40.0),
+        Transform::from_xyz(0.0, -10.5, 0.0),
+    ));
+    let points = [
+        Vec3::new(0.0, 0.0, -2.0),
+        Vec3::new(0.0, 0.0, 2.0),
+        Vec3::new(LENGTH, 0.0, -2.0),
+        Vec3::new(LENGTH, 0.0, 2.0),
+        Vec3::new(LENGTH, LENGTH * slope, -2.0),
+        Vec3::new(LENGTH, LENGTH * slope, 2.0),
+    ];
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::convex_hull(points.to_vec()).unwrap(),
+        Transform::default(),
+        OneWayPlatform,
+    ));
+    let puppeteer = app
+        .world_mut()
+        .spawn((
+            Puppeteer::default(),
+            Transform::from_xyz(1.0, slope + PUPPET_HALF_HEIGHT + 0.1, 0.0),
+        ))
+        .id();
+
+    for tick in 0..64 {
+        let mut input = app
+            .world_mut()
+            .get_mut::<PuppeteerInput>(puppeteer)
+            .unwrap();
+        input.speed_multiplier = 1.0;
+        input.move_amount(Vec3::X);
+        app.update();
+
+        let translation = app.world().get::<Transform>(puppeteer).unwrap().translation;
+        let ramp_height = translation.x.clamp(0.0, LENGTH) * slope;
+        assert!(
+            translation.y > ramp_height,
+            "puppeteer fell through the ramp at {translation} on tick {tick}"
+        );
+    }
+    let translation = app.world().get::<Transform>(puppeteer).unwrap().translation;
+    assert!(translation.x > 3.0, "puppeteer only got to {translation}");
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use avian3d::prelude::*;
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use common::TIME_STEP;
+use puppeteer::{
+    PuppeteerPlugin,
+    external::ExternalVelocity,
+    modifiers::MovementModifiers,
+    puppet::{Grounded, Puppet, SurfaceData},
+    puppeteer::{AirJumpCount, CoyoteTime, JumpProfile, Puppeteer, PuppeteerInput},
+    trajectory::{JumpInput, JumpPrediction, predict_jump},
+};
+
+/// Predicts the next jump of the puppeteer without any input
+#[allow(clippy::complexity)]
+fn predict(app: &mut App, entity: Entity) -> JumpPrediction {
+    app.world_mut()
+        .run_system_once(
+            move |spatial_query: SpatialQuery,
+                  surface_query: Query<SurfaceData>,
+                  query: Query<(
+                &Puppeteer,
+                &Puppet,
+                &ExternalVelocity,
+                &MovementModifiers,
+                &Collider,
+                &Transform,
+                Has<Grounded>,
+                Option<&CoyoteTime>,
+                Option<&AirJumpCount>,
+            )>| {
+                let (
+                    puppeteer,
+                    puppet,
+                    external,
+                    modifiers,
+                    collider,
+                    transform,
+                    is_grounded,
+                    coyote_time,
+                    air_jump_count,
+                ) = query.get(entity).unwrap();
+                let jump_index = puppeteer
+                    .next_jump_index(is_grounded, coyote_time, air_jump_count)
+                    .expect("the puppeteer can jump");
+                predict_jump(
+                    &spatial_query,
+                    &SpatialQueryFilter::default().with_excluded_entities([entity]),
+                    &surface_query,
+                    collider,
+                    transform.translation,
+                    puppeteer,
+                    puppet,
+                    external,
+                    modifiers,
+                    jump_index,
+                    JumpInput {
+                        move_direction: Vec3::ZERO,
+                        speed_multiplier: 0.0,
+                        hold_time: None,
+                    },
+                    TIME_STEP,
+                    5.0,
+                )
+            },
+        )
+        .unwrap()
+}
+
+#[test]
+fn predicts_air_jumps() {
+    let mut app = common::create_app(PuppeteerPlugin);
+    app.world_mut().spawn((
+        RigidBody::Static,
+        Collider::cuboid(40.0, 1.0, 40.0),
+        Transform::from_xyz(0.0, -0.5, 0.0),
+    ));
+    let entity = app
+        .world_mut()
+        .spawn((
+            Puppeteer {
+                air_jumps: vec![JumpProfile {
+                    height: 2.0,
+                    horizontal_boost: 3.0,
+                    gravity_multiplier: 0.5,
+                    ..default()
+                }],
+                ..default()
+            },
+            Puppet {
+                target_velocity: Vec3::X * 4.0,
+                ..default()
+            },
+            Transform::from_xyz(0.0, 3.0, 0.0),
+        ))
+        .id();
+
+    // Fall until the coyote time is over
+    for _ in 0..16 {
+        app.update();
+    }
+    app.world_mut()
+        .get_mut::<ExternalVelocity>(entity)
+        .unwrap()
+        .apply_impulse(Vec3::new(0.0, 1.0, 3.0));
+    let prediction = predict(&mut app, entity);
+    let landing = prediction.landing.expect("the jump lands on the floor");
+
+    app.world_mut()
+        .get_mut::<PuppeteerInput>(entity)
+        .unwrap()
+        .start_jump();
+    app.update();
+    assert_eq!(
+        app.world().get::<AirJumpCount>(entity).map(|jumps| jumps.0),
+        Some(1),
+        "the puppeteer didn't air jump"
+    );
+    let mut time = TIME_STEP;
+    while app.world().get::<Grounded>(entity).is_none() {
+        app.update();
+        time += TIME_STEP;
+        assert!(time < 5.0, "the puppeteer never landed");
+    }
+
+    let position = app.world().get::<Transform>(entity).unwrap().translation;
+    assert!(
+        position.xz().distance(landing.position.xz()) < 0.1,
+        "predicted landing at {}, landed at {position}",
+        landing.position
+    );
+    assert!(
+        (time - landing.time).abs() < TIME_STEP * 1.5,
+        "predicted landing after {}s, landed after {time}s",
+        landing.time
+    );
+}